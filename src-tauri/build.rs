@@ -1,3 +1,38 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// アクセシビリティ監査でページに読み込む axe-core（npm の axe-core の `axe.min.js`）
+const AXE_CORE_VER: &str = "4.10.3";
+const AXE_CORE_PATH: &str = "resources/axe.min.js";
+
 fn main() {
+    bundle_axe_core();
     tauri_build::build()
 }
+
+// axe-core を `OUT_DIR` にコピーする
+// 見つからない・版が違う場合はビルドを止めず、空のスクリプトにして監査だけを失敗させる
+fn bundle_axe_core() {
+    println!("cargo:rerun-if-changed={}", AXE_CORE_PATH);
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("axe.min.js");
+
+    let script = match fs::read_to_string(AXE_CORE_PATH) {
+        Ok(script) if script.starts_with(&format!("/*! axe v{}", AXE_CORE_VER)) => script,
+        Ok(_) => {
+            println!(
+                "cargo:warning={} is not axe-core {}. Accessibility audits are disabled.",
+                AXE_CORE_PATH, AXE_CORE_VER
+            );
+            String::new()
+        }
+        Err(_) => {
+            println!(
+                "cargo:warning={} is missing. Accessibility audits are disabled.",
+                AXE_CORE_PATH
+            );
+            String::new()
+        }
+    };
+    fs::write(&out_path, script).unwrap();
+}
@@ -1,70 +1,680 @@
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use serde::Serialize;
 use std::fs;
 use tauri::command;
 use tauri::State;
+use thirtyfour::prelude::*;
 
-use crate::config::constants::{APPIUM_TIMEOUT, SCREENSHOT_DIR};
+use crate::config::constants::{APPIUM_TIMEOUT, BLOCKING_PROXY_PORT, SCREENSHOT_DIR};
 use crate::services::appium::AppiumState;
-use crate::services::screenshot::{capture_full_page, combine_screenshots};
-use crate::services::webrdiver::create_webdriver;
+use crate::services::audit::{run_accessibility_audit, AuditSummary};
+use crate::services::blocking::{block_urls, count_blocked_requests, BlockingProxy};
+use crate::services::cdp::CdpSession;
+use crate::services::consent::{dismiss_consent_banner, ConsentResult};
+use crate::services::console::{collect_console_log, ConsoleSummary, CONSOLE_TRACKER_SCRIPT};
+use crate::services::device::adb::{remove_reverse, reverse_port};
+use crate::services::dom::{add_init_script, freeze_clock_script, get_dom_snapshot};
+use crate::services::har::{collect_network_log, read_performance_log};
+use crate::services::hooks::{load_domain_hooks, run_hooks, HookPoint};
+use crate::services::image::{apply_masks, draw_highlights, draw_outlines};
+use crate::services::native::{capture_native_screen, NativeCaptureOptions};
+use crate::services::pdf::print_pdf;
+use crate::services::performance::collect_performance_metrics;
+use crate::services::recording::{start_recording, stop_recording};
+use crate::services::screenshot::{
+    capture_full_page, capture_full_page_cdp, combine_screenshots, CaptureBackend, CaptureMethod,
+    CaptureOptions, FullPageCapture, TileTiming,
+};
+use crate::services::states::capture_states;
+use crate::services::webrdiver::{create_app_driver, create_webdriver};
+use crate::services::webview::{
+    list_webview_contexts, open_deep_link, switch_to_webview, WebviewTarget,
+};
 use crate::utils::wait::{wait_for_appium_ready, wait_for_page_load};
 
+// フロントエンドに返す撮影結果
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenshotResult {
+    pub path: String,
+    pub pdf_path: Option<String>,
+    pub video_path: Option<String>,
+    pub console_summary: Option<ConsoleSummary>,
+    pub har_path: Option<String>,
+    pub dom_snapshot_path: Option<String>,
+    pub metrics_path: Option<String>,
+    // アクセシビリティ監査の結果（JSON）と枠線を描いた画像
+    pub audit_path: Option<String>,
+    pub audit_image_path: Option<String>,
+    pub audit_summary: Option<AuditSummary>,
+    pub warnings: Vec<String>,
+    // 塗りつぶしたテキストの数
+    pub redacted_matches: u64,
+    // ページ全体を一度に撮影したか、スクロールして結合したか
+    pub capture_method: CaptureMethod,
+    // `adb screencap` で撮影したときの所要時間
+    pub tile_timing: Option<TileTiming>,
+    // 描画途中で撮り直したスクリーンショットの数
+    pub tile_retries: u32,
+    pub state_sets: Vec<String>,
+    pub blocked_requests: Option<u64>,
+    pub consent: Option<ConsentResult>,
+}
+
 #[command]
 pub async fn take_screenshot(
     state: State<'_, AppiumState>,
     url: String,
-    hidden_elements: String,
-) -> Result<(), String> {
+    mut options: CaptureOptions,
+) -> Result<ScreenshotResult, String> {
     debug!("take_screenshot");
 
-    // Appiumサーバーを起動
-    if let Err(e) = state.start_appium().await {
-        error!("Failed to start Appium: {}", e);
-        return Err(format!("Failed to start Appium: {}", e));
+    let formatted_url =
+        if url.is_empty() || url.starts_with("http://") || url.starts_with("https://") {
+            url
+        } else {
+            format!("http://{}", url)
+        };
+
+    info!("Taking screenshot of {}", formatted_url);
+    let capture = match options.backend {
+        CaptureBackend::Appium | CaptureBackend::Webview => {
+            capture_with_appium(&state, &formatted_url, &mut options).await?
+        }
+        CaptureBackend::CdpAdb => capture_with_cdp(&formatted_url, &options).await?,
+    };
+    for warning in &capture.warnings {
+        warn!("{}", warning);
     }
 
-    // Appiumの起動を待機
-    if let Err(e) = wait_for_appium_ready(APPIUM_TIMEOUT).await {
-        error!("Appium did not start in time: {}", e);
-        return Err(e);
+    let mut final_screenshot = combine_screenshots(capture.screenshots)?;
+
+    // 個人情報などをマスク
+    if !capture.mask_regions.is_empty() {
+        info!("Applying {} masks...", capture.mask_regions.len());
+        final_screenshot = apply_masks(&final_screenshot, &capture.mask_regions)?;
     }
 
-    info!("Taking screenshot of {}", url);
-    let driver = create_webdriver("safari").await?;
+    // アクセシビリティの違反箇所は別の画像に描画（深刻度の高い順に番号を振る）
+    let audit_image = match &capture.audit {
+        Some(audit) => {
+            let regions: Vec<_> = audit.regions.iter().collect();
+            Some(draw_outlines(&final_screenshot, &regions)?)
+        }
+        None => None,
+    };
+
+    // レビュー用の枠線と番号を描画
+    if !capture.highlight_regions.is_empty() {
+        info!("Drawing {} highlights...", capture.highlight_regions.len());
+        final_screenshot = draw_highlights(&final_screenshot, &capture.highlight_regions)?;
+    }
+
+    let screenshot_path = SCREENSHOT_DIR.join("screenshot.png");
+    fs::write(&screenshot_path, final_screenshot)
+        .map_err(|e| format!("Failed to save screenshot: {}", e))?;
+
+    info!("Saved screenshot to {:?}", screenshot_path);
+
+    // 監査結果（.a11y.json）と違反箇所を描いた画像（.a11y.png）を保存
+    let (audit_path, audit_image_path, audit_summary) = match (capture.audit, audit_image) {
+        (Some(audit), Some(image)) => {
+            let audit_path = screenshot_path.with_extension("a11y.json");
+            let json = serde_json::to_string_pretty(&audit.report)
+                .map_err(|e| format!("Failed to serialize accessibility audit: {}", e))?;
+            fs::write(&audit_path, json)
+                .map_err(|e| format!("Failed to save accessibility audit: {}", e))?;
+            let image_path = screenshot_path.with_extension("a11y.png");
+            fs::write(&image_path, image)
+                .map_err(|e| format!("Failed to save annotated screenshot: {}", e))?;
+            info!("Saved accessibility audit to {:?}", audit_path);
+            (
+                Some(audit_path.display().to_string()),
+                Some(image_path.display().to_string()),
+                Some(AuditSummary::new(&audit.report)),
+            )
+        }
+        _ => (None, None, None),
+    };
+
+    // PDF はスクリーンショットと同じ名前で保存
+    let pdf_path = match capture.pdf {
+        Some(pdf) => {
+            let pdf_path = screenshot_path.with_extension("pdf");
+            fs::write(&pdf_path, pdf).map_err(|e| format!("Failed to save PDF: {}", e))?;
+            info!("Saved PDF to {:?}", pdf_path);
+            Some(pdf_path.display().to_string())
+        }
+        None => None,
+    };
+
+    // 録画もスクリーンショットと同じ名前で保存
+    let video_path = match capture.recording {
+        Some(video) => {
+            let video_path = screenshot_path.with_extension("mp4");
+            fs::write(&video_path, video)
+                .map_err(|e| format!("Failed to save screen recording: {}", e))?;
+            info!("Saved screen recording to {:?}", video_path);
+            Some(video_path.display().to_string())
+        }
+        None => None,
+    };
+
+    // コンソールのログもスクリーンショットと同じ名前で保存
+    let console_summary = match capture.console_entries {
+        Some(entries) => {
+            let console_path = screenshot_path.with_extension("console.json");
+            let json = serde_json::to_string_pretty(&entries)
+                .map_err(|e| format!("Failed to serialize console log: {}", e))?;
+            fs::write(&console_path, json)
+                .map_err(|e| format!("Failed to save console log: {}", e))?;
+            info!("Saved console log to {:?}", console_path);
+            Some(ConsoleSummary::new(&entries))
+        }
+        None => None,
+    };
+
+    // DOM（.html）、表示中のテキスト（.txt）、タイトルなど（.meta.json）を保存
+    let dom_snapshot_path = match capture.dom_snapshot {
+        Some(snapshot) => {
+            let html_path = screenshot_path.with_extension("html");
+            fs::write(&html_path, &snapshot.html)
+                .map_err(|e| format!("Failed to save DOM snapshot: {}", e))?;
+            fs::write(screenshot_path.with_extension("txt"), &snapshot.text)
+                .map_err(|e| format!("Failed to save visible text: {}", e))?;
+            let meta = serde_json::to_string_pretty(&snapshot)
+                .map_err(|e| format!("Failed to serialize page metadata: {}", e))?;
+            fs::write(screenshot_path.with_extension("meta.json"), meta)
+                .map_err(|e| format!("Failed to save page metadata: {}", e))?;
+            info!("Saved DOM snapshot to {:?}", html_path);
+            Some(html_path.display().to_string())
+        }
+        None => None,
+    };
+
+    // 読み込みの速さを保存
+    let metrics_path = match capture.performance_metrics {
+        Some(metrics) => {
+            let metrics_path = screenshot_path.with_extension("metrics.json");
+            let json = serde_json::to_string_pretty(&metrics)
+                .map_err(|e| format!("Failed to serialize performance metrics: {}", e))?;
+            fs::write(&metrics_path, json)
+                .map_err(|e| format!("Failed to save performance metrics: {}", e))?;
+            info!("Saved performance metrics to {:?}", metrics_path);
+            Some(metrics_path.display().to_string())
+        }
+        None => None,
+    };
+
+    // 通信の一覧を HAR で保存
+    let har_path = match capture.har {
+        Some(har) => {
+            let har_path = screenshot_path.with_extension("har");
+            let json = serde_json::to_string_pretty(&har)
+                .map_err(|e| format!("Failed to serialize HAR: {}", e))?;
+            fs::write(&har_path, json).map_err(|e| format!("Failed to save HAR: {}", e))?;
+            info!("Saved HAR to {:?}", har_path);
+            Some(har_path.display().to_string())
+        }
+        None => None,
+    };
+
+    Ok(ScreenshotResult {
+        path: screenshot_path.display().to_string(),
+        pdf_path,
+        video_path,
+        console_summary,
+        har_path,
+        dom_snapshot_path,
+        metrics_path,
+        audit_path,
+        audit_image_path,
+        audit_summary,
+        warnings: capture.warnings,
+        redacted_matches: capture.redacted_matches,
+        capture_method: capture.method,
+        tile_timing: capture.tile_timing,
+        tile_retries: capture.tile_retries,
+        state_sets: capture.state_sets,
+        blocked_requests: capture.blocked_requests,
+        consent: capture.consent,
+    })
+}
 
-    let formatted_url = if url.starts_with("http://") || url.starts_with("https://") {
-        url
+// Appium（WebDriver）で開いてスクロールしながら撮影
+async fn capture_with_appium(
+    state: &AppiumState,
+    url: &str,
+    options: &mut CaptureOptions,
+) -> Result<FullPageCapture, String> {
+    start_appium_server(state).await?;
+
+    // Chrome 以外はローカルのプロキシを経由させて通信をブロック（Chrome は DevTools を使う）
+    let proxy = if options.blocking.enabled
+        && options.backend == CaptureBackend::Appium
+        && options.browser != "chrome"
+    {
+        Some(start_blocking_proxy(options).await?)
     } else {
-        format!("http://{}", url)
+        None
     };
 
-    driver
-        .goto(&formatted_url)
-        .await
-        .map_err(|e| format!("Failed to navigate to URL: {}", e))?;
+    let mut result = match open_session(options, proxy.is_some()).await {
+        Ok(driver) => {
+            let result = capture_page(&driver, url, options).await;
+
+            // セッションを終了
+            if let Err(e) = driver.quit().await {
+                error!("Failed to quit session: {}", e);
+            }
+            result
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Some(proxy) = proxy {
+        let blocked = proxy.stop();
+        if options.browser == "firefox" {
+            if let Err(e) = remove_reverse(options.android_serial.as_deref(), BLOCKING_PROXY_PORT) {
+                error!("Failed to remove port reverse: {}", e);
+            }
+        }
+        if let Ok(capture) = &mut result {
+            capture.blocked_requests = Some(blocked);
+            if blocked == 0 && options.browser == "safari" {
+                capture.warn(if options.blocking.allow_lan {
+                    format!(
+                        "No requests were blocked. Set the iPhone's Wi-Fi HTTP proxy to port {} of this computer",
+                        BLOCKING_PROXY_PORT
+                    )
+                } else {
+                    "No requests were blocked. Enable blocking.allowLan to use the proxy from an iPhone over Wi-Fi".to_string()
+                });
+            }
+        }
+    }
+
+    // Appiumサーバーを停止
+    if let Err(e) = state.stop_appium() {
+        error!("Failed to stop Appium: {}", e);
+    }
+
+    result
+}
+
+// ブロック用のプロキシを起動し、Firefox（Android）からは `localhost` で届くようにする
+// LAN で待ち受けるのは Safari で明示的に許可した場合だけ
+async fn start_blocking_proxy(options: &CaptureOptions) -> Result<BlockingProxy, String> {
+    let allow_lan = options.browser == "safari" && options.blocking.allow_lan;
+    let proxy = BlockingProxy::start(
+        BLOCKING_PROXY_PORT,
+        options.blocking.url_patterns(),
+        allow_lan,
+    )
+    .await?;
+    if options.browser == "firefox" {
+        if let Err(e) = reverse_port(options.android_serial.as_deref(), BLOCKING_PROXY_PORT) {
+            proxy.stop();
+            return Err(e);
+        }
+    }
+    Ok(proxy)
+}
+
+// ブラウザ、またはアプリ内の WebView を操作するセッションを開始
+async fn open_session(options: &CaptureOptions, use_proxy: bool) -> Result<WebDriver, String> {
+    match options.backend {
+        CaptureBackend::Webview => {
+            create_app_driver(&options.webview.app, options.android_serial.as_deref()).await
+        }
+        _ => {
+            let proxy_port = use_proxy.then_some(BLOCKING_PROXY_PORT);
+            create_webdriver(&options.browser, proxy_port).await
+        }
+    }
+}
+
+// URL を開いて（WebView ならその中で）撮影
+async fn capture_page(
+    driver: &WebDriver,
+    url: &str,
+    options: &mut CaptureOptions,
+) -> Result<FullPageCapture, String> {
+    let mut warnings = vec![];
+
+    // ディープリンクで開いた場合や URL がない場合は、WebView に表示中のページをそのまま撮る
+    let mut navigate = !url.is_empty();
+    if options.backend == CaptureBackend::Webview {
+        if options.webview.deep_link && navigate {
+            open_deep_link(driver, url, &options.webview.app).await?;
+            navigate = false;
+        }
+        let context = switch_to_webview(driver, &options.webview).await?;
+        let contexts = list_webview_contexts(driver).await?;
+        if contexts.len() > 1 {
+            warnings.push(format!(
+                "Captured {} of {} WebViews: {}",
+                context,
+                contexts.len(),
+                contexts.join(", ")
+            ));
+        }
+    }
+
+    // Chrome は DevTools で通信をブロック（WebView は対象外）
+    let block_with_devtools = options.blocking.enabled
+        && options.browser == "chrome"
+        && options.backend == CaptureBackend::Appium;
+    if options.blocking.enabled && options.backend == CaptureBackend::Webview {
+        warnings.push("Request blocking is not supported for WebViews".to_string());
+    } else if block_with_devtools {
+        if let Err(e) = block_urls(driver, &options.blocking.url_patterns()).await {
+            warnings.push(e);
+        }
+    }
+
+    // Chrome はページのスクリプトより先に時刻と乱数の固定・コンソールの記録を仕込む
+    // （それ以外は読み込み後に仕込むため、それまでの動きは対象外）
+    let init_script =
+        options.browser == "chrome" && options.backend == CaptureBackend::Appium && navigate;
+    if options.determinism.enabled {
+        if !init_script {
+            warnings.push(
+                "Date and Math.random are frozen after the page's scripts have run".to_string(),
+            );
+        } else if let Err(e) =
+            add_init_script(driver, &freeze_clock_script(&options.determinism)).await
+        {
+            warnings.push(format!("Failed to freeze clock before navigation: {}", e));
+        }
+    }
+    let mut console_tracked_from_start = false;
+    if options.console_log {
+        if !init_script {
+            warnings.push(
+                "Console messages are recorded only after the page has started loading".to_string(),
+            );
+        } else if let Err(e) = add_init_script(driver, CONSOLE_TRACKER_SCRIPT).await {
+            warnings.push(format!(
+                "Failed to install console tracker before navigation: {}",
+                e
+            ));
+        } else {
+            console_tracked_from_start = true;
+        }
+    }
+
+    let url = if navigate {
+        driver
+            .goto(url)
+            .await
+            .map_err(|e| format!("Failed to navigate to URL: {}", e))?;
+        url.to_string()
+    } else {
+        driver
+            .current_url()
+            .await
+            .map(|current| current.to_string())
+            .unwrap_or_default()
+    };
 
     // ページの完全読み込みを待つ
-    wait_for_page_load(&driver, &formatted_url).await?;
+    warnings.extend(
+        wait_for_page_load(
+            driver,
+            &url,
+            &options.determinism,
+            &options.wait_strategies,
+            options.console_log,
+            options.performance_metrics,
+        )
+        .await?,
+    );
+
+    // 読み込み直後の速さ（スクロールや操作によるレイアウトのずれを含めない）
+    let mut performance_metrics = None;
+    if options.performance_metrics {
+        match collect_performance_metrics(driver).await {
+            Ok(metrics) => performance_metrics = Some(metrics),
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    // Cookie 同意バナーを閉じる
+    let mut consent = None;
+    if options.consent.enabled {
+        match dismiss_consent_banner(driver, &options.consent).await {
+            Ok(result) => consent = result,
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    // リクエストで指定されたフックにドメインごとのフックを追加
+    options.hooks.extend(load_domain_hooks(&url));
+    warnings.extend(run_hooks(driver, &options.hooks, HookPoint::AfterLoad).await);
+
+    // 撮影中の画面を録画（失敗しても撮影は続ける）
+    let mut recording = false;
+    if options.recording.enabled {
+        match start_recording(driver, &options.recording).await {
+            Ok(()) => recording = true,
+            Err(e) => warnings.push(e),
+        }
+    }
 
     // スクロールしながらスクリーンショットを撮影
-    let screenshots = capture_full_page(&driver, &hidden_elements).await?;
+    let capture = capture_full_page(driver, options).await;
+
+    // 撮影に失敗しても録画は止める
+    let video = if recording {
+        match stop_recording(driver).await {
+            Ok(video) => Some(video),
+            Err(e) => {
+                warnings.push(e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut capture = capture?;
+    warnings.append(&mut capture.warnings);
+    capture.consent = consent;
+    capture.recording = video;
+    capture.performance_metrics = performance_metrics;
+
+    // 撮影時点の DOM とテキスト
+    if options.dom_snapshot {
+        match get_dom_snapshot(driver).await {
+            Ok(snapshot) => capture.dom_snapshot = Some(snapshot),
+            Err(e) => warnings.push(format!("Failed to get DOM snapshot: {}", e)),
+        }
+    }
+
+    // axe-core でアクセシビリティを監査
+    if options.audit.enabled {
+        match run_accessibility_audit(driver, &options.audit).await {
+            Ok(audit) => capture.audit = Some(audit),
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    // 同じページを PDF でも保存
+    if options.pdf.enabled {
+        match print_pdf(driver, &options.pdf).await {
+            Ok(pdf) => capture.pdf = Some(pdf),
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    // 要素の状態ごとのスクリーンショット
+    if !options.state_captures.is_empty() {
+        let (state_sets, state_warnings) =
+            capture_states(driver, &options.browser, &options.state_captures).await;
+        capture.state_sets = state_sets;
+        warnings.extend(state_warnings);
+    }
+
+    // 撮影中までのコンソール出力とエラー
+    if options.console_log {
+        match collect_console_log(driver, &options.browser, console_tracked_from_start).await {
+            Ok(entries) => capture.console_entries = Some(entries),
+            Err(e) => warnings.push(e),
+        }
+    }
 
-    // セッションを終了
-    if let Err(e) = driver.quit().await {
-        error!("Failed to quit session: {}", e);
+    // Chrome の通信のログ（読み出すと消えるので HAR とブロックした数で共有する）
+    let mut performance_log = vec![];
+    if options.browser == "chrome" && (options.network_log || block_with_devtools) {
+        match read_performance_log(driver).await {
+            Ok(log) => performance_log = log,
+            Err(e) => debug!("Performance log is not available: {}", e),
+        }
     }
 
+    // 撮影までの通信
+    if options.network_log {
+        match collect_network_log(driver, &performance_log).await {
+            Ok((har, har_warnings)) => {
+                capture.har = Some(har);
+                warnings.extend(har_warnings);
+            }
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    if block_with_devtools {
+        capture.blocked_requests = Some(count_blocked_requests(&performance_log));
+    }
+    capture.warnings = warnings;
+
+    Ok(capture)
+}
+
+// adb で転送した Android Chrome の DevTools に直接つないで撮影
+async fn capture_with_cdp(url: &str, options: &CaptureOptions) -> Result<FullPageCapture, String> {
+    let mut session = CdpSession::connect(options.android_serial.as_deref()).await?;
+
+    let result = match session.navigate(url).await {
+        Ok(()) => capture_full_page_cdp(&mut session, options).await,
+        Err(e) => Err(e),
+    };
+
+    // 接続を閉じてポート転送を解除
+    if let Err(e) = session.close().await {
+        error!("Failed to close DevTools session: {}", e);
+    }
+
+    result
+}
+
+// アプリのネイティブ画面をスワイプしながら撮影
+#[command]
+pub async fn take_app_screenshot(
+    state: State<'_, AppiumState>,
+    options: NativeCaptureOptions,
+) -> Result<ScreenshotResult, String> {
+    debug!("take_app_screenshot");
+
+    start_appium_server(&state).await?;
+
+    let result = match create_app_driver(&options.app, options.android_serial.as_deref()).await {
+        Ok(driver) => {
+            let result = capture_native_screen(&driver, &options).await;
+
+            // セッションを終了
+            if let Err(e) = driver.quit().await {
+                error!("Failed to quit session: {}", e);
+            }
+            result
+        }
+        Err(e) => Err(e),
+    };
+
     // Appiumサーバーを停止
     if let Err(e) = state.stop_appium() {
         error!("Failed to stop Appium: {}", e);
     }
 
-    let final_screenshot = combine_screenshots(screenshots)?;
-    let screenshot_path = SCREENSHOT_DIR.join("screenshot.png");
-    fs::write(&screenshot_path, final_screenshot)
+    let capture = result?;
+    for warning in &capture.warnings {
+        warn!("{}", warning);
+    }
+
+    let screenshot_path = SCREENSHOT_DIR.join("app_screenshot.png");
+    fs::write(&screenshot_path, combine_screenshots(capture.screenshots)?)
         .map_err(|e| format!("Failed to save screenshot: {}", e))?;
 
     info!("Saved screenshot to {:?}", screenshot_path);
 
+    Ok(ScreenshotResult {
+        path: screenshot_path.display().to_string(),
+        pdf_path: None,
+        video_path: None,
+        console_summary: None,
+        har_path: None,
+        dom_snapshot_path: None,
+        metrics_path: None,
+        audit_path: None,
+        audit_image_path: None,
+        audit_summary: None,
+        warnings: capture.warnings,
+        redacted_matches: 0,
+        capture_method: capture.method,
+        tile_timing: None,
+        tile_retries: 0,
+        state_sets: vec![],
+        blocked_requests: None,
+        consent: None,
+    })
+}
+
+// アプリ内の WebView の context 一覧（撮影する WebView を選ぶため）
+#[command]
+pub async fn list_webviews(
+    state: State<'_, AppiumState>,
+    target: WebviewTarget,
+    android_serial: Option<String>,
+) -> Result<Vec<String>, String> {
+    debug!("list_webviews");
+
+    start_appium_server(&state).await?;
+
+    let result = match create_app_driver(&target.app, android_serial.as_deref()).await {
+        Ok(driver) => {
+            let result = list_webview_contexts(&driver).await;
+
+            // セッションを終了
+            if let Err(e) = driver.quit().await {
+                error!("Failed to quit session: {}", e);
+            }
+            result
+        }
+        Err(e) => Err(e),
+    };
+
+    // Appiumサーバーを停止
+    if let Err(e) = state.stop_appium() {
+        error!("Failed to stop Appium: {}", e);
+    }
+
+    result
+}
+
+// Appiumサーバーを起動して準備ができるまで待つ
+async fn start_appium_server(state: &AppiumState) -> Result<(), String> {
+    // Appiumサーバーを起動
+    if let Err(e) = state.start_appium().await {
+        error!("Failed to start Appium: {}", e);
+        return Err(format!("Failed to start Appium: {}", e));
+    }
+
+    // Appiumの起動を待機
+    if let Err(e) = wait_for_appium_ready(APPIUM_TIMEOUT).await {
+        error!("Appium did not start in time: {}", e);
+        return Err(e);
+    }
+
     Ok(())
 }
@@ -31,6 +31,13 @@ pub static LOG_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
         .unwrap_or_else(|_| HOME_DIR.join(BASE_DIR).join("log"))
 });
 
+// ドメインごとに注入する CSS / JavaScript（`hooks/<host>/<point>.css` など）
+pub static HOOKS_DIR: LazyLock<PathBuf> = LazyLock::new(|| HOME_DIR.join(BASE_DIR).join("hooks"));
+
+// 同梱のルールより先に試す Cookie 同意バナーのルール
+pub static CONSENT_RULES_FILE: LazyLock<PathBuf> =
+    LazyLock::new(|| HOME_DIR.join(BASE_DIR).join("consent_rules.json"));
+
 pub const LOG_FILE_NAME: &str = "scshoki.log";
 pub const LOG_ROTATE_BASE: u32 = 1;
 pub const LOG_ROTATE_COUNT: u32 = 3;
@@ -42,6 +49,18 @@ pub const APPIUM_PORT: &str = "4723";
 pub static APPIUM_SERVER_URL: LazyLock<String> =
     LazyLock::new(|| format!("http://127.0.0.1:{APPIUM_PORT}"));
 pub const APPIUM_TIMEOUT: Duration = Duration::from_secs(10);
+pub const WAIT_STRATEGY_TIMEOUT: Duration = Duration::from_secs(30);
+// 読み込み後に Cookie 同意バナーが現れるまで待つ時間
+pub const CONSENT_BANNER_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Android Chrome の DevTools（`adb forward` の転送先）
+pub const CHROME_DEVTOOLS_SOCKET: &str = "localabstract:chrome_devtools_remote";
+pub const CDP_TIMEOUT: Duration = Duration::from_secs(30);
+// アプリ内の WebView が現れるまで待つ時間
+pub const WEBVIEW_TIMEOUT: Duration = Duration::from_secs(20);
+
+// 通信をブロックするプロキシのポート（iPhone は Wi-Fi の HTTP プロキシにこの PC とこのポートを設定）
+pub const BLOCKING_PROXY_PORT: u16 = 8899;
 
 pub static BINARY_DIR: LazyLock<PathBuf> = LazyLock::new(|| HOME_DIR.join(BASE_DIR).join("bin"));
 pub static NODE_DIR: LazyLock<PathBuf> = LazyLock::new(|| BINARY_DIR.join("node"));
@@ -10,7 +10,7 @@ use std::sync::{Arc, Mutex};
 use tauri::{Manager, State, WindowEvent};
 
 use commands::appium::{start_appium, stop_appium};
-use commands::screenshot::take_screenshot;
+use commands::screenshot::{list_webviews, take_app_screenshot, take_screenshot};
 use config::constants::{BINARY_DIR, HOST_ARCH, HOST_OS};
 use config::env::add_to_path;
 use infrastructure::binaries::init_binaries;
@@ -71,6 +71,8 @@ fn main() {
             start_appium,
             stop_appium,
             take_screenshot,
+            take_app_screenshot,
+            list_webviews,
         ])
         .run(tauri::generate_context!())
         .expect("error while running Scshoki");
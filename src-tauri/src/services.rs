@@ -1,6 +1,20 @@
 pub mod appium;
+pub mod audit;
+pub mod blocking;
+pub mod cdp;
+pub mod consent;
+pub mod console;
 pub mod device;
 pub mod dom;
+pub mod gesture;
+pub mod har;
+pub mod hooks;
 pub mod image;
+pub mod native;
+pub mod pdf;
+pub mod performance;
+pub mod recording;
 pub mod screenshot;
+pub mod states;
 pub mod webrdiver;
+pub mod webview;
@@ -0,0 +1,216 @@
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use thirtyfour::prelude::*;
+
+use crate::services::dom::{DocumentRect, DOCUMENT_RECTS_SCRIPT};
+use crate::services::image::HighlightRegion;
+
+// ブラウザ用にビルド済みの axe-core（`build.rs` が `resources/axe.min.js` をコピーする。なければ空）
+const AXE_CORE_SCRIPT: &str = include_str!(concat!(env!("OUT_DIR"), "/axe.min.js"));
+
+// axe-core を実行して違反だけを返す
+const RUN_AXE_SCRIPT: &str = r#"
+    const done = arguments[arguments.length - 1];
+    axe.run(document, arguments[0]).then(
+        (result) => done({
+            url: result.url,
+            timestamp: result.timestamp,
+            testEngine: result.testEngine,
+            violations: result.violations,
+        }),
+        (error) => done({ error: String(error) }),
+    );
+"#;
+
+// axe の `target`（iframe・Shadow DOM は入れ子のセレクター）から要素を探す関数
+const RESOLVE_TARGET_SCRIPT: &str = r#"
+    function resolveTarget(target) {
+        let root = document, element = null;
+        for (let i = 0; i < target.length; i++) {
+            const chain = Array.isArray(target[i]) ? target[i] : [target[i]];
+            for (let j = 0; j < chain.length; j++) {
+                element = root && root.querySelector(chain[j]);
+                if (!element) return null;
+                if (j < chain.length - 1) root = element.shadowRoot;
+            }
+            // 別オリジンの iframe は辿れない
+            if (i < target.length - 1) root = element.contentDocument;
+        }
+        return element;
+    }
+"#;
+
+// axe の深刻度（低い順）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Impact {
+    Minor,
+    Moderate,
+    Serious,
+    Critical,
+}
+
+impl Impact {
+    fn color(self) -> &'static str {
+        match self {
+            Impact::Critical => "#d50000",
+            Impact::Serious => "#ff6d00",
+            Impact::Moderate => "#ffab00",
+            Impact::Minor => "#2962ff",
+        }
+    }
+}
+
+// 読み込み後に axe-core でアクセシビリティを監査する
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AuditOptions {
+    pub enabled: bool,
+    // 実行するルールのタグ（`wcag2a`、`best-practice` など。空なら全て）
+    pub tags: Vec<String>,
+}
+
+// 監査結果（`violations` の各要素に画像の番号 `label` を付けたもの）
+#[derive(Debug, Default)]
+pub struct AccessibilityAudit {
+    pub report: Value,
+    // 深刻度の高い順に並べた枠線
+    pub regions: Vec<HighlightRegion>,
+}
+
+// 撮影結果に載せる件数
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditSummary {
+    // 違反したルールの数
+    pub violations: usize,
+    // 違反した要素の数（深刻度ごと）
+    pub critical: usize,
+    pub serious: usize,
+    pub moderate: usize,
+    pub minor: usize,
+}
+
+impl AuditSummary {
+    pub fn new(report: &Value) -> Self {
+        let mut summary = AuditSummary::default();
+        for violation in report["violations"].as_array().into_iter().flatten() {
+            summary.violations += 1;
+            let nodes = violation["nodes"].as_array().map_or(0, Vec::len);
+            match parse_impact(&violation["impact"]) {
+                Some(Impact::Critical) => summary.critical += nodes,
+                Some(Impact::Serious) => summary.serious += nodes,
+                Some(Impact::Moderate) => summary.moderate += nodes,
+                Some(Impact::Minor) | None => summary.minor += nodes,
+            }
+        }
+        summary
+    }
+}
+
+// axe-core を読み込んで実行し、違反した要素の矩形をドキュメント座標で取得
+pub async fn run_accessibility_audit(
+    driver: &WebDriver,
+    options: &AuditOptions,
+) -> Result<AccessibilityAudit, String> {
+    info!("Running accessibility audit...");
+    if AXE_CORE_SCRIPT.is_empty() {
+        return Err("axe-core is not bundled in this build".to_string());
+    }
+    driver
+        .execute(AXE_CORE_SCRIPT, vec![])
+        .await
+        .map_err(|e| format!("Failed to inject axe-core: {}", e))?;
+
+    let mut run_options = json!({ "resultTypes": ["violations"] });
+    if !options.tags.is_empty() {
+        run_options["runOnly"] = json!({ "type": "tag", "values": options.tags });
+    }
+    let mut report = driver
+        .execute_async(RUN_AXE_SCRIPT, vec![run_options])
+        .await
+        .map_err(|e| format!("Failed to run axe-core: {}", e))?
+        .json()
+        .clone();
+    if let Some(error) = report["error"].as_str() {
+        return Err(format!("axe-core failed: {}", error));
+    }
+
+    // 違反した要素を深刻度の高い順に並べて番号を振る
+    let mut nodes = vec![];
+    for (v, violation) in report["violations"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let impact = parse_impact(&violation["impact"]).unwrap_or(Impact::Minor);
+        for (n, node) in violation["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let impact = parse_impact(&node["impact"]).unwrap_or(impact);
+            nodes.push((impact, v, n, node["target"].clone()));
+        }
+    }
+    nodes.sort_by_key(|node| Reverse(node.0));
+
+    let targets: Vec<Value> = nodes.iter().map(|node| node.3.clone()).collect();
+    let rects = get_target_rects(driver, targets).await?;
+
+    let mut regions = vec![];
+    for ((impact, v, n, _), rect) in nodes.into_iter().zip(rects) {
+        let Some(rect) = rect else {
+            debug!(
+                "Violation target is not rendered: {}",
+                report["violations"][v]["id"]
+            );
+            continue;
+        };
+        regions.push(HighlightRegion {
+            rect,
+            color: impact.color().to_string(),
+            label: true,
+        });
+        report["violations"][v]["nodes"][n]["label"] = json!(regions.len());
+    }
+    info!(
+        "Found {} accessibility violations ({} elements outlined).",
+        report["violations"].as_array().map_or(0, Vec::len),
+        regions.len()
+    );
+
+    Ok(AccessibilityAudit { report, regions })
+}
+
+async fn get_target_rects(
+    driver: &WebDriver,
+    targets: Vec<Value>,
+) -> Result<Vec<Option<DocumentRect>>, String> {
+    let script = format!(
+        r#"
+        {}
+        {}
+        return arguments[0].map(target => {{
+            const element = resolveTarget(target);
+            return element ? documentRects([element], false)[0] || null : null;
+        }});
+        "#,
+        RESOLVE_TARGET_SCRIPT, DOCUMENT_RECTS_SCRIPT
+    );
+
+    let result = driver
+        .execute(&script, vec![json!(targets)])
+        .await
+        .map_err(|e| format!("Failed to get violation rects: {}", e))?;
+    serde_json::from_value(result.json().clone())
+        .map_err(|e| format!("Failed to parse violation rects: {}", e))
+}
+
+fn parse_impact(value: &Value) -> Option<Impact> {
+    serde_json::from_value(value.clone()).ok()
+}
@@ -0,0 +1,396 @@
+use log::{debug, info};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use thirtyfour::extensions::cdp::ChromeDevTools;
+use thirtyfour::prelude::*;
+use tokio::io::{copy_bidirectional, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{lookup_host, TcpListener, TcpStream};
+use tokio::task::{JoinHandle, JoinSet};
+
+// 広告・トラッカー・チャットウィジェットのブロックリスト（EasyList 形式）
+const BUNDLED_BLOCKLIST: &str = include_str!("../../resources/blocklist.txt");
+
+const MAX_HEADER_SIZE: usize = 64 * 1024;
+const BLOCKED_RESPONSE: &[u8] =
+    b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+// 中継するポート（それ以外への中継は断る）
+const UPSTREAM_PORTS: [u16; 2] = [443, 80];
+
+// 撮影中の通信をブロックする設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RequestBlocking {
+    pub enabled: bool,
+    // 同梱のブロックリストを使う
+    pub bundled_list: bool,
+    // EasyList 形式のパターン（`||example.com^`、`/ads/*` など）
+    pub patterns: Vec<String>,
+    // iPhone の Wi-Fi プロキシから使えるよう、プロキシを LAN でも待ち受ける（Safari のみ）
+    pub allow_lan: bool,
+}
+
+impl Default for RequestBlocking {
+    fn default() -> Self {
+        RequestBlocking {
+            enabled: false,
+            bundled_list: true,
+            patterns: vec![],
+            allow_lan: false,
+        }
+    }
+}
+
+impl RequestBlocking {
+    // CDP の `Network.setBlockedURLs` に渡せる `*` だけのパターンに変換
+    pub fn url_patterns(&self) -> Vec<String> {
+        let bundled = BUNDLED_BLOCKLIST.lines().filter(|_| self.bundled_list);
+        let mut patterns: Vec<String> = bundled
+            .chain(self.patterns.iter().map(String::as_str))
+            .flat_map(to_url_patterns)
+            .collect();
+        patterns.sort();
+        patterns.dedup();
+        patterns
+    }
+}
+
+// EasyList のルールを URL のパターンに変換（要素の非表示・例外のルールは対象外）
+fn to_url_patterns(rule: &str) -> Vec<String> {
+    let rule = rule.trim();
+    if rule.is_empty()
+        || rule.starts_with('!')
+        || rule.starts_with('[')
+        || rule.starts_with("@@")
+        || rule.contains('#')
+    {
+        return vec![];
+    }
+    // `$` 以降はオプション（third-party など）
+    let rule = rule.split('$').next().unwrap_or_default();
+    let (rule, end_anchor) = match rule.strip_suffix('|') {
+        Some(rule) => (rule, true),
+        None => (rule, false),
+    };
+    let finish = |pattern: String| {
+        if end_anchor || pattern.ends_with('*') {
+            pattern
+        } else {
+            pattern + "*"
+        }
+    };
+
+    if let Some(rest) = rule.strip_prefix("||") {
+        // ドメイン名（サブドメインを含む）で一致
+        let split = rest.find(['/', '^', '*', ':']).unwrap_or(rest.len());
+        let (domain, tail) = rest.split_at(split);
+        let tail = match tail.strip_prefix('^') {
+            Some(tail) => format!("/{}", tail),
+            None => tail.to_string(),
+        }
+        .replace('^', "*");
+        return vec![
+            finish(format!("*://{}{}", domain, tail)),
+            finish(format!("*://*.{}{}", domain, tail)),
+        ];
+    }
+
+    let pattern = match rule.strip_prefix('|') {
+        Some(rest) => rest.replace('^', "*"),
+        None => format!("*{}", rule.trim_start_matches('*').replace('^', "*")),
+    };
+    if pattern.trim_matches('*').is_empty() {
+        return vec![];
+    }
+    vec![finish(pattern)]
+}
+
+// `*` だけのワイルドカードで URL を照合
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text) = (pattern.as_bytes(), text.as_bytes());
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+fn is_blocked(url: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| glob_match(pattern, url))
+}
+
+// Chrome は DevTools でブロックする（ページを開く前に呼ぶ）
+pub async fn block_urls(driver: &WebDriver, patterns: &[String]) -> Result<(), String> {
+    info!("Blocking {} URL patterns via DevTools", patterns.len());
+    let devtools = ChromeDevTools::new(driver.handle.clone());
+    devtools
+        .execute_cdp("Network.enable")
+        .await
+        .map_err(|e| format!("Failed to enable DevTools network domain: {}", e))?;
+    devtools
+        .execute_cdp_with_params("Network.setBlockedURLs", json!({ "urls": patterns }))
+        .await
+        .map_err(|e| format!("Failed to block URLs: {}", e))?;
+    Ok(())
+}
+
+// パフォーマンスログから DevTools でブロックした通信を数える
+pub fn count_blocked_requests(performance_log: &[Value]) -> u64 {
+    performance_log
+        .iter()
+        .filter(|message| {
+            message["method"] == "Network.loadingFailed"
+                && message["params"]["blockedReason"] == "inspector"
+        })
+        .count() as u64
+}
+
+// Chrome 以外のブラウザ用に、パターンに一致する通信を断るローカルの HTTP プロキシ
+// HTTPS は CONNECT のホスト名しか分からないため、ドメイン単位のパターンだけが効く
+pub struct BlockingProxy {
+    blocked: Arc<AtomicU64>,
+    task: JoinHandle<()>,
+}
+
+impl BlockingProxy {
+    // 通常は `localhost` だけで待ち受け、`allow_lan` なら端末から Wi-Fi 経由でも使えるようにする
+    pub async fn start(port: u16, patterns: Vec<String>, allow_lan: bool) -> Result<Self, String> {
+        let address = if allow_lan { "0.0.0.0" } else { "127.0.0.1" };
+        let listener = TcpListener::bind((address, port))
+            .await
+            .map_err(|e| format!("Failed to start blocking proxy on port {}: {}", port, e))?;
+
+        let blocked = Arc::new(AtomicU64::new(0));
+        let counter = blocked.clone();
+        let patterns = Arc::new(patterns);
+        let task = tokio::spawn(async move {
+            // 停止時にこのタスクごと破棄され、中継中の接続も中断される
+            let mut connections = JoinSet::new();
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        let Ok((client, _)) = accepted else {
+                            continue;
+                        };
+                        let patterns = patterns.clone();
+                        let counter = counter.clone();
+                        connections.spawn(async move {
+                            if let Err(e) = relay(client, &patterns, &counter).await {
+                                debug!("Proxy connection closed: {}", e);
+                            }
+                        });
+                    }
+                    // 終わった接続を片付ける
+                    Some(_) = connections.join_next() => {}
+                }
+            }
+        });
+        info!("Started blocking proxy on {}:{}", address, port);
+
+        Ok(BlockingProxy { blocked, task })
+    }
+
+    // 停止してブロックした通信の数を返す
+    pub fn stop(self) -> u64 {
+        self.task.abort();
+        let blocked = self.blocked.load(Ordering::Relaxed);
+        info!("Stopped blocking proxy ({} requests blocked)", blocked);
+        blocked
+    }
+}
+
+// 1つの接続を中継する（HTTP は1リクエストごとに接続を切り、毎回ブロックの判定をする）
+async fn relay(mut client: TcpStream, patterns: &[String], blocked: &AtomicU64) -> io::Result<()> {
+    let mut buffer = vec![];
+    let header_end = loop {
+        let mut chunk = [0u8; 4096];
+        let read = client.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(position) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break position + 4;
+        }
+        if buffer.len() > MAX_HEADER_SIZE {
+            return Err(io::Error::other("Request header is too large"));
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let body = &buffer[header_end..];
+
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(io::Error::other(format!(
+            "Invalid request: {}",
+            request_line
+        )));
+    };
+
+    // HTTPS はトンネルを張る前にホスト名で判定
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let Some((host, port)) = split_host_port(target, 443) else {
+            return Err(io::Error::other(format!("Invalid target: {}", target)));
+        };
+        if is_blocked(&format!("https://{}/", host), patterns) {
+            debug!("Blocked: {}", target);
+            blocked.fetch_add(1, Ordering::Relaxed);
+            return client.write_all(BLOCKED_RESPONSE).await;
+        }
+        let Some(mut upstream) = connect_upstream(host, port).await? else {
+            debug!("Refused: {}", target);
+            return client.write_all(BLOCKED_RESPONSE).await;
+        };
+        client
+            .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+            .await?;
+        upstream.write_all(body).await?;
+        copy_bidirectional(&mut client, &mut upstream).await?;
+        return Ok(());
+    }
+
+    if is_blocked(target, patterns) {
+        debug!("Blocked: {}", target);
+        blocked.fetch_add(1, Ordering::Relaxed);
+        return client.write_all(BLOCKED_RESPONSE).await;
+    }
+
+    let Some(rest) = target.strip_prefix("http://") else {
+        return Err(io::Error::other(format!("Unsupported target: {}", target)));
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    let Some((host, port)) = split_host_port(authority, 80) else {
+        return Err(io::Error::other(format!("Invalid target: {}", target)));
+    };
+
+    // 絶対 URL をパスに戻し、接続を使い回さないようにする
+    let mut request = format!("{} {} {}\r\n", method, path, version);
+    for line in lines.filter(|line| !line.is_empty()) {
+        let name = line.split(':').next().unwrap_or_default().trim();
+        if !name.eq_ignore_ascii_case("connection")
+            && !name.eq_ignore_ascii_case("proxy-connection")
+        {
+            request.push_str(line);
+            request.push_str("\r\n");
+        }
+    }
+    request.push_str("Connection: close\r\n\r\n");
+
+    let Some(mut upstream) = connect_upstream(host, port).await? else {
+        debug!("Refused: {}", target);
+        return client.write_all(BLOCKED_RESPONSE).await;
+    };
+    upstream.write_all(request.as_bytes()).await?;
+    upstream.write_all(body).await?;
+    copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+// `host:port`（IPv6 は `[::1]:443`）をホスト名とポートに分ける（ポートがなければ `default_port`）
+fn split_host_port(authority: &str, default_port: u16) -> Option<(&str, u16)> {
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (host, port.parse().ok()?),
+        _ => (authority, default_port),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    (!host.is_empty()).then_some((host, port))
+}
+
+// 許可したポートの、インターネット上のアドレスにだけ接続する（断った場合は `None`）
+// 名前解決した結果に接続するので、解決し直してローカルに向けられることもない
+async fn connect_upstream(host: &str, port: u16) -> io::Result<Option<TcpStream>> {
+    if !UPSTREAM_PORTS.contains(&port) {
+        return Ok(None);
+    }
+    let addresses: Vec<SocketAddr> = lookup_host((host, port))
+        .await?
+        .filter(|address| is_public_address(address.ip()))
+        .collect();
+    if addresses.is_empty() {
+        return Ok(None);
+    }
+    TcpStream::connect(addresses.as_slice()).await.map(Some)
+}
+
+// ループバック・リンクローカル・プライベートなどのアドレスでない
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast())
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_host_port_adds_default_port() {
+        assert_eq!(
+            split_host_port("example.com", 443),
+            Some(("example.com", 443))
+        );
+        assert_eq!(
+            split_host_port("example.com:80", 443),
+            Some(("example.com", 80))
+        );
+        assert_eq!(split_host_port("[::1]:443", 80), Some(("::1", 443)));
+        assert_eq!(split_host_port("[::1]", 80), Some(("::1", 80)));
+        assert_eq!(split_host_port("example.com:abc", 80), None);
+    }
+
+    #[test]
+    fn local_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "192.168.1.10",
+            "169.254.169.254",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+        assert!(is_public_address("93.184.216.34".parse().unwrap()));
+        assert!(is_public_address("2606:2800:220:1::".parse().unwrap()));
+    }
+}
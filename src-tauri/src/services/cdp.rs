@@ -0,0 +1,229 @@
+use base64::prelude::*;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info};
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout, Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use crate::config::constants::CDP_TIMEOUT;
+use crate::services::device::adb::{forward_devtools, remove_forward};
+
+// Android Chrome の開いているタブに adb 経由で接続した CDP セッション
+pub struct CdpSession {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    next_id: u64,
+    // 応答を待つ間に届いたイベント
+    events: Vec<Value>,
+    serial: Option<String>,
+    port: u16,
+}
+
+impl CdpSession {
+    // `adb forward` で DevTools ソケットを転送し、最初のタブに WebSocket で接続
+    pub async fn connect(serial: Option<&str>) -> Result<Self, String> {
+        let port = forward_devtools(serial)?;
+        info!("Forwarded Chrome DevTools to tcp:{}", port);
+
+        match open_page_socket(port).await {
+            Ok(socket) => Ok(CdpSession {
+                socket,
+                next_id: 0,
+                events: vec![],
+                serial: serial.map(str::to_string),
+                port,
+            }),
+            Err(e) => {
+                let _ = remove_forward(serial, port);
+                Err(e)
+            }
+        }
+    }
+
+    // コマンドを送信して同じ id の応答を待つ（途中のイベントは `events` に溜める）
+    pub async fn send(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let id = self.next_id;
+        debug!("CDP {} ({})", method, id);
+
+        let message = json!({ "id": id, "method": method, "params": params });
+        self.socket
+            .send(Message::text(message.to_string()))
+            .await
+            .map_err(|e| format!("Failed to send {}: {}", method, e))?;
+
+        let response = timeout(CDP_TIMEOUT, async {
+            while let Some(message) = self.socket.next().await {
+                let text = match message {
+                    Ok(Message::Text(text)) => text,
+                    Ok(_) => continue,
+                    Err(e) => return Err(format!("WebSocket error: {}", e)),
+                };
+                let response: Value = serde_json::from_str(text.as_str())
+                    .map_err(|e| format!("Invalid CDP message: {}", e))?;
+                if response["id"] == id {
+                    return Ok(response);
+                }
+                if response.get("method").is_some() {
+                    self.events.push(response);
+                }
+            }
+            Err("DevTools connection closed".to_string())
+        })
+        .await
+        .map_err(|_| format!("Timed out waiting for {}", method))??;
+
+        if let Some(error) = response.get("error") {
+            return Err(format!("{} failed: {}", method, error["message"]));
+        }
+        Ok(response["result"].clone())
+    }
+
+    // スクリプトを実行して値を返す（Promise は解決を待つ）
+    pub async fn evaluate(&mut self, expression: &str) -> Result<Value, String> {
+        let result = self
+            .send(
+                "Runtime.evaluate",
+                json!({
+                    "expression": expression,
+                    "returnByValue": true,
+                    "awaitPromise": true,
+                }),
+            )
+            .await?;
+
+        if let Some(exception) = result.get("exceptionDetails") {
+            return Err(format!("Script failed: {}", exception["text"]));
+        }
+        Ok(result["result"]["value"].clone())
+    }
+
+    // 条件に合うイベントが届くまで待つ（溜めてあるイベントから先に探す）
+    async fn wait_for_event(
+        &mut self,
+        name: &str,
+        matches: impl Fn(&Value) -> bool,
+    ) -> Result<Value, String> {
+        if let Some(index) = self.events.iter().position(&matches) {
+            return Ok(self.events.remove(index));
+        }
+
+        timeout(CDP_TIMEOUT, async {
+            while let Some(message) = self.socket.next().await {
+                let text = match message {
+                    Ok(Message::Text(text)) => text,
+                    Ok(_) => continue,
+                    Err(e) => return Err(format!("WebSocket error: {}", e)),
+                };
+                let event: Value = serde_json::from_str(text.as_str())
+                    .map_err(|e| format!("Invalid CDP message: {}", e))?;
+                if matches(&event) {
+                    return Ok(event);
+                }
+            }
+            Err("DevTools connection closed".to_string())
+        })
+        .await
+        .map_err(|_| format!("Timed out waiting for {}", name))?
+    }
+
+    // ページを開いて `document.readyState` が `complete` になるまで待つ
+    // 直後は前のページが残っていることがあるため、先に新しいドキュメントの `load` を待つ
+    pub async fn navigate(&mut self, url: &str) -> Result<(), String> {
+        info!("Navigating to {} over CDP", url);
+        self.events.clear();
+        self.send("Page.enable", json!({})).await?;
+        self.send("Page.setLifecycleEventsEnabled", json!({ "enabled": true }))
+            .await?;
+        let result = self.send("Page.navigate", json!({ "url": url })).await?;
+        if let Some(error) = result["errorText"].as_str() {
+            return Err(format!("Failed to navigate to URL: {}", error));
+        }
+
+        // 同じドキュメント内の移動（`#` のみ）は `loaderId` がない
+        if let Some(loader_id) = result["loaderId"].as_str() {
+            self.wait_for_event("page load", |event| {
+                event["method"] == "Page.lifecycleEvent"
+                    && event["params"]["name"] == "load"
+                    && event["params"]["loaderId"] == loader_id
+            })
+            .await?;
+        }
+        self.events.clear();
+
+        let start_time = Instant::now();
+        while start_time.elapsed() < CDP_TIMEOUT {
+            if let Ok(state) = self.evaluate("document.readyState").await {
+                if state == "complete" {
+                    info!("Page fully loaded.");
+                    return Ok(());
+                }
+            }
+            sleep(Duration::from_millis(500)).await;
+        }
+
+        Err("Page did not load in time".to_string())
+    }
+
+    // ドキュメント全体の大きさ（CSS px）
+    pub async fn get_content_size(&mut self) -> Result<(f64, f64), String> {
+        let metrics = self.send("Page.getLayoutMetrics", json!({})).await?;
+        let size = &metrics["cssContentSize"];
+        match (size["width"].as_f64(), size["height"].as_f64()) {
+            (Some(width), Some(height)) if height > 0.0 => Ok((width, height)),
+            _ => Err("Failed to retrieve page height.".to_string()),
+        }
+    }
+
+    // ページ全体を一度に撮影
+    pub async fn capture_full_page(&mut self) -> Result<Vec<u8>, String> {
+        let (width, height) = self.get_content_size().await?;
+        info!("Capturing {}x{} over CDP...", width, height);
+
+        let result = self
+            .send(
+                "Page.captureScreenshot",
+                json!({
+                    "format": "png",
+                    "captureBeyondViewport": true,
+                    "clip": { "x": 0, "y": 0, "width": width, "height": height, "scale": 1 },
+                }),
+            )
+            .await?;
+
+        let data = result["data"]
+            .as_str()
+            .ok_or("Unexpected response from Page.captureScreenshot")?;
+        BASE64_STANDARD
+            .decode(data)
+            .map_err(|e| format!("Failed to decode screenshot: {}", e))
+    }
+
+    // WebSocket を閉じてポート転送を解除
+    pub async fn close(mut self) -> Result<(), String> {
+        let _ = self.socket.close(None).await;
+        remove_forward(self.serial.as_deref(), self.port)
+    }
+}
+
+// `/json/list` からタブを探して WebSocket で接続
+async fn open_page_socket(port: u16) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, String> {
+    let targets: Vec<Value> = reqwest::get(format!("http://127.0.0.1:{}/json/list", port))
+        .await
+        .map_err(|e| format!("Failed to list Chrome tabs: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Chrome tabs: {}", e))?;
+
+    let url = targets
+        .iter()
+        .find(|target| target["type"] == "page")
+        .and_then(|target| target["webSocketDebuggerUrl"].as_str())
+        .ok_or("No open Chrome tab found. Open Chrome on the device first.")?;
+
+    let (socket, _) = connect_async(url)
+        .await
+        .map_err(|e| format!("Failed to connect to DevTools: {}", e))?;
+    Ok(socket)
+}
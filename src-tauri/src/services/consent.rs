@@ -0,0 +1,152 @@
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use thirtyfour::prelude::*;
+use tokio::time::{sleep, Duration, Instant};
+
+use crate::config::constants::{CONSENT_BANNER_TIMEOUT, CONSENT_RULES_FILE};
+
+// OneTrust・Cookiebot・TrustArc など、よく使われる同意管理ツールのルール
+const BUNDLED_CONSENT_RULES: &str = include_str!("../../resources/consent_rules.json");
+
+// 表示中のバナーに一致したルールのボタンを押す（ボタンがなければバナーを非表示にする）
+const DISMISS_CONSENT_SCRIPT: &str = r#"
+    const [rules, action] = arguments;
+    const visible = (e) => {
+        const rect = e.getBoundingClientRect();
+        const style = getComputedStyle(e);
+        return rect.width > 0 && rect.height > 0 && style.display !== "none" && style.visibility !== "hidden";
+    };
+    const find = (selector) => {
+        if (!selector) return null;
+        try {
+            return Array.from(document.querySelectorAll(selector)).find(visible) || null;
+        } catch (e) {
+            return null;
+        }
+    };
+
+    for (const rule of rules) {
+        if (!find(rule.detect)) continue;
+
+        const button = action === "accept" ? find(rule.accept) : action === "reject" ? find(rule.reject) : null;
+        if (button) {
+            button.click();
+            return { rule: rule.name, action };
+        }
+
+        for (const selector of rule.hide.length > 0 ? rule.hide : [rule.detect]) {
+            try {
+                document.querySelectorAll(selector).forEach(e => e.style.setProperty("display", "none", "important"));
+            } catch (e) {}
+        }
+        return { rule: rule.name, action: "hide" };
+    }
+    return null;
+"#;
+
+// バナーへの対応
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentAction {
+    // 「同意する」を押す
+    #[default]
+    Accept,
+    // 「拒否する」を押す
+    Reject,
+    // ボタンを押さずにバナーを非表示にする
+    Hide,
+}
+
+// 同意管理ツールごとのセレクター
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentRule {
+    pub name: String,
+    // 表示されていればこのルールを使う
+    pub detect: String,
+    #[serde(default)]
+    pub accept: Option<String>,
+    #[serde(default)]
+    pub reject: Option<String>,
+    // 非表示にする要素（未指定なら `detect` の要素）
+    #[serde(default)]
+    pub hide: Vec<String>,
+}
+
+// 読み込み後に Cookie 同意バナーを閉じる
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ConsentOptions {
+    pub enabled: bool,
+    pub action: ConsentAction,
+    // ルールファイルと同梱のルールより先に試すルール
+    pub rules: Vec<ConsentRule>,
+}
+
+// 一致したルールと実際の対応
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsentResult {
+    pub rule: String,
+    pub action: ConsentAction,
+}
+
+// 指定したルール、`~/.scshoki/consent_rules.json`、同梱のルールの順に並べる
+fn load_consent_rules(options: &ConsentOptions) -> Result<Vec<ConsentRule>, String> {
+    let mut rules = options.rules.clone();
+
+    if CONSENT_RULES_FILE.exists() {
+        let json = fs::read_to_string(&*CONSENT_RULES_FILE)
+            .map_err(|e| format!("Failed to read {:?}: {}", *CONSENT_RULES_FILE, e))?;
+        let custom: Vec<ConsentRule> = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse {:?}: {}", *CONSENT_RULES_FILE, e))?;
+        info!(
+            "Loaded {} consent rules from {:?}",
+            custom.len(),
+            *CONSENT_RULES_FILE
+        );
+        rules.extend(custom);
+    }
+
+    let bundled: Vec<ConsentRule> = serde_json::from_str(BUNDLED_CONSENT_RULES)
+        .map_err(|e| format!("Failed to parse bundled consent rules: {}", e))?;
+    rules.extend(bundled);
+
+    Ok(rules)
+}
+
+// バナーが現れるまで少し待ちながらルールを試し、一致したルールを返す
+pub async fn dismiss_consent_banner(
+    driver: &WebDriver,
+    options: &ConsentOptions,
+) -> Result<Option<ConsentResult>, String> {
+    let rules = load_consent_rules(options)?;
+    let args = vec![json!(rules), json!(options.action)];
+
+    let start_time = Instant::now();
+    loop {
+        let result = driver
+            .execute(DISMISS_CONSENT_SCRIPT, args.clone())
+            .await
+            .map_err(|e| format!("Failed to dismiss consent banner: {}", e))?;
+
+        if let Some(result) = serde_json::from_value::<Option<ConsentResult>>(result.json().clone())
+            .map_err(|e| format!("Failed to parse consent result: {}", e))?
+        {
+            info!(
+                "Dismissed consent banner: {} ({:?})",
+                result.rule, result.action
+            );
+            // 閉じるアニメーションを待つ
+            sleep(Duration::from_millis(500)).await;
+            return Ok(Some(result));
+        }
+
+        if start_time.elapsed() >= CONSENT_BANNER_TIMEOUT {
+            debug!("No consent banner found");
+            return Ok(None);
+        }
+        sleep(Duration::from_millis(500)).await;
+    }
+}
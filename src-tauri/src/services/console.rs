@@ -0,0 +1,187 @@
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thirtyfour::prelude::*;
+
+use crate::services::webrdiver::SessionCommand;
+
+// コンソール出力・例外・未処理の Promise の拒否を記録する（何度実行しても1回だけ仕込む）
+pub const CONSOLE_TRACKER_SCRIPT: &str = r#"
+    if (!window.__scshokiConsole) {
+        const entries = window.__scshokiConsole = [];
+        const push = (entry) => {
+            if (entries.length < 1000) entries.push({ ...entry, timestamp: performance.now() });
+        };
+        const format = (value) => {
+            if (value instanceof Error) return value.stack || String(value);
+            if (typeof value === "object") {
+                try { return JSON.stringify(value); } catch (e) { return String(value); }
+            }
+            return String(value);
+        };
+
+        for (const level of ["log", "info", "warn", "error", "debug"]) {
+            const original = console[level];
+            console[level] = function (...args) {
+                push({ type: "console", level, message: args.map(format).join(" ") });
+                return original.apply(this, args);
+            };
+        }
+
+        // キャプチャ段階で受けると画像などの読み込み失敗も取れる
+        window.addEventListener("error", (event) => {
+            if (event.target !== window && event.target) {
+                const url = event.target.currentSrc || event.target.src || event.target.href;
+                push({ type: "resource_error", level: "error", message: `Failed to load ${url}`, source: url });
+                return;
+            }
+            push({
+                type: "exception",
+                level: "error",
+                message: event.message,
+                source: event.filename,
+                line: event.lineno,
+                column: event.colno,
+                stack: event.error && event.error.stack,
+            });
+        }, true);
+
+        window.addEventListener("unhandledrejection", (event) => {
+            const reason = event.reason;
+            push({
+                type: "unhandled_rejection",
+                level: "error",
+                message: reason && reason.message ? reason.message : format(reason),
+                stack: reason && reason.stack,
+            });
+        });
+    }
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleEntryKind {
+    Console,
+    Exception,
+    UnhandledRejection,
+    // 画像・スクリプトなどの読み込み失敗
+    ResourceError,
+    // ブラウザのログ（Chrome の `goog:loggingPrefs`）
+    Browser,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleEntry {
+    #[serde(rename = "type")]
+    pub kind: ConsoleEntryKind,
+    pub level: String,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<String>,
+    #[serde(default)]
+    pub timestamp: f64,
+}
+
+// 撮影結果に載せる件数
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleSummary {
+    pub total: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub exceptions: usize,
+    pub unhandled_rejections: usize,
+}
+
+impl ConsoleSummary {
+    pub fn new(entries: &[ConsoleEntry]) -> Self {
+        let count_level = |level: &str| {
+            entries
+                .iter()
+                .filter(|e| e.level.eq_ignore_ascii_case(level))
+                .count()
+        };
+        let count_kind = |kind: ConsoleEntryKind| entries.iter().filter(|e| e.kind == kind).count();
+
+        ConsoleSummary {
+            total: entries.len(),
+            // Chrome のログは `SEVERE` が error 相当
+            errors: count_level("error") + count_level("severe"),
+            warnings: count_level("warn") + count_level("warning"),
+            exceptions: count_kind(ConsoleEntryKind::Exception),
+            unhandled_rejections: count_kind(ConsoleEntryKind::UnhandledRejection),
+        }
+    }
+}
+
+// ページに記録した内容と、取得できればブラウザのログを集める
+// `tracked_from_start` はページを開く前に記録を仕込めたか（ブラウザのログとの重複を除く）
+pub async fn collect_console_log(
+    driver: &WebDriver,
+    browser: &str,
+    tracked_from_start: bool,
+) -> Result<Vec<ConsoleEntry>, String> {
+    let value = driver
+        .execute("return window.__scshokiConsole || [];", vec![])
+        .await
+        .map_err(|e| format!("Failed to collect console log: {}", e))?
+        .json()
+        .clone();
+    let mut entries: Vec<ConsoleEntry> =
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse console log: {}", e))?;
+
+    // CSP 違反などページ内から取れないものは Chrome のログにしか残らない
+    if browser == "chrome" {
+        match SessionCommand::post("se/log", json!({ "type": "browser" }))
+            .send(driver)
+            .await
+        {
+            Ok(logs) => {
+                let browser_entries: Vec<ConsoleEntry> = logs
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|log| ConsoleEntry {
+                        kind: ConsoleEntryKind::Browser,
+                        level: log["level"].as_str().unwrap_or("INFO").to_string(),
+                        message: log["message"].as_str().unwrap_or_default().to_string(),
+                        source: log["source"].as_str().map(str::to_string),
+                        line: None,
+                        column: None,
+                        stack: None,
+                        timestamp: log["timestamp"].as_f64().unwrap_or_default(),
+                    })
+                    .filter(|entry| !tracked_from_start || !duplicates_tracker(entry, &entries))
+                    .collect();
+                entries.extend(browser_entries);
+            }
+            Err(e) => debug!("Browser log is not available: {}", e),
+        }
+    }
+
+    info!("Collected {} console entries.", entries.len());
+    Ok(entries)
+}
+
+// ページ内でも記録した console・例外・同じ URL の読み込み失敗（fetch / XHR の失敗は残す）
+fn duplicates_tracker(entry: &ConsoleEntry, tracked: &[ConsoleEntry]) -> bool {
+    match entry.source.as_deref() {
+        Some("console-api" | "javascript") => true,
+        // `https://example.com/a.png - Failed to load resource: ...`
+        Some("network") => {
+            let url = entry.message.split_whitespace().next().unwrap_or_default();
+            tracked.iter().any(|t| {
+                t.kind == ConsoleEntryKind::ResourceError && t.source.as_deref() == Some(url)
+            })
+        }
+        _ => false,
+    }
+}
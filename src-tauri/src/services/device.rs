@@ -1,3 +1,4 @@
+pub mod adb;
 pub mod density;
 pub mod detect;
 pub mod os;
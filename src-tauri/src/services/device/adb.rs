@@ -0,0 +1,95 @@
+use std::net::TcpListener;
+use std::process::Command;
+
+use crate::config::constants::CHROME_DEVTOOLS_SOCKET;
+
+// シリアルを指定した adb コマンド（未指定なら接続中の1台）
+pub fn adb_command(serial: Option<&str>) -> Command {
+    let mut command = Command::new("adb");
+    if let Some(serial) = serial {
+        command.arg("-s").arg(serial);
+    }
+    command
+}
+
+// Chrome の DevTools ソケットを空いているローカルポートに転送
+pub fn forward_devtools(serial: Option<&str>) -> Result<u16, String> {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map_err(|e| format!("Failed to find a free port: {}", e))?
+        .port();
+
+    let output = adb_command(serial)
+        .arg("forward")
+        .arg(format!("tcp:{}", port))
+        .arg(CHROME_DEVTOOLS_SOCKET)
+        .output()
+        .map_err(|e| format!("Failed to execute adb forward: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "adb forward failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(port)
+}
+
+pub fn remove_forward(serial: Option<&str>, port: u16) -> Result<(), String> {
+    adb_command(serial)
+        .arg("forward")
+        .arg("--remove")
+        .arg(format!("tcp:{}", port))
+        .output()
+        .map_err(|e| format!("Failed to execute adb forward --remove: {}", e))?;
+    Ok(())
+}
+
+// 端末の `localhost:port` への接続をこの PC の同じポートに転送
+pub fn reverse_port(serial: Option<&str>, port: u16) -> Result<(), String> {
+    let output = adb_command(serial)
+        .arg("reverse")
+        .arg(format!("tcp:{}", port))
+        .arg(format!("tcp:{}", port))
+        .output()
+        .map_err(|e| format!("Failed to execute adb reverse: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "adb reverse failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn remove_reverse(serial: Option<&str>, port: u16) -> Result<(), String> {
+    adb_command(serial)
+        .arg("reverse")
+        .arg("--remove")
+        .arg(format!("tcp:{}", port))
+        .output()
+        .map_err(|e| format!("Failed to execute adb reverse --remove: {}", e))?;
+    Ok(())
+}
+
+// 端末の画面を PNG で取得（Appium を経由しないので速い）
+pub fn screencap(serial: Option<&str>) -> Result<Vec<u8>, String> {
+    let output = adb_command(serial)
+        .arg("exec-out")
+        .arg("screencap")
+        .arg("-p")
+        .output()
+        .map_err(|e| format!("Failed to execute adb screencap: {}", e))?;
+
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(format!(
+            "adb screencap failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(output.stdout)
+}
@@ -1,8 +1,13 @@
 use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::HashMap;
 use std::error::Error;
+use thirtyfour::extensions::cdp::ChromeDevTools;
 use thirtyfour::prelude::*;
 
+use crate::services::image::MaskEffect;
+
 pub async fn get_page_metrics(driver: &WebDriver) -> Result<HashMap<String, f64>, Box<dyn Error>> {
     debug!("Getting page metrics...");
 
@@ -10,6 +15,7 @@ pub async fn get_page_metrics(driver: &WebDriver) -> Result<HashMap<String, f64>
         const innerHeight = window.innerHeight;
         const totalScrollHeight = document.documentElement.scrollHeight
         return {
+            innerWidth: window.innerWidth,
             innerHeight: window.innerHeight,
             totalScrollHeight: document.documentElement.scrollHeight,
             scrollSteps: Math.ceil(totalScrollHeight / innerHeight),
@@ -29,49 +35,608 @@ pub async fn get_page_metrics(driver: &WebDriver) -> Result<HashMap<String, f64>
     Ok(result_map)
 }
 
-// 指定した要素を `display: none;` に設定し非表示にする
-pub async fn hide_elements(driver: &WebDriver, selectors: &str) -> Result<(), Box<dyn Error>> {
-    debug!("Hiding elements: {}", selectors);
+// 非表示にするタイミング
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HidePolicy {
+    // 最初のスクリーンショットから非表示
+    Always,
+    // 最初のスクロール以降は非表示（ヘッダー向け）
+    #[default]
+    AfterFirst,
+    // 最後のスクリーンショットでのみ表示（フッター・CTAバー向け）
+    LastOnly,
+    // メトリクス取得前から非表示にし、一度も出力に含めない（`display: none` でも高さがずれない）
+    NeverInOutput,
+}
+
+impl HidePolicy {
+    // `index` 枚目（1始まり）のスクリーンショットで非表示にするかどうか
+    pub fn hides_tile(&self, index: u32, is_last: bool) -> bool {
+        match self {
+            HidePolicy::Always | HidePolicy::NeverInOutput => true,
+            HidePolicy::AfterFirst => index > 1,
+            HidePolicy::LastOnly => !is_last,
+        }
+    }
+}
+
+// 非表示にする方法（`display: none` はレイアウトが変わるので注意）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HideMethod {
+    #[default]
+    Visibility,
+    Display,
+    Opacity,
+}
+
+impl HideMethod {
+    // 設定するCSSプロパティと値
+    fn css(&self) -> (&'static str, &'static str) {
+        match self {
+            HideMethod::Visibility => ("visibility", "hidden"),
+            HideMethod::Display => ("display", "none"),
+            HideMethod::Opacity => ("opacity", "0"),
+        }
+    }
+}
+
+// セレクタの種類
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectorKind {
+    #[default]
+    Css,
+    Xpath,
+    // テキストを含む要素
+    Text,
+}
+
+// 非表示・マスク対象の要素の指定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ElementTarget {
+    pub selector: String,
+    pub kind: SelectorKind,
+    // open な Shadow Root の中も探す
+    pub pierce_shadow: bool,
+    // 同一オリジンの iframe の中も探す
+    pub include_frames: bool,
+}
+
+impl ElementTarget {
+    pub fn is_empty(&self) -> bool {
+        self.selector.trim().is_empty()
+    }
+
+    // スクリプトの引数として渡す値
+    pub fn to_arg(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HideRule {
+    #[serde(flatten)]
+    pub target: ElementTarget,
+    #[serde(default)]
+    pub policy: HidePolicy,
+    #[serde(default)]
+    pub method: HideMethod,
+}
+
+// `ElementTarget` に一致する要素を探す関数（`arguments` で渡した値をそのまま受け取る）
+// 不正なセレクタは一致なしとして扱う（理由は `selector_error` で取得する）
+pub const FIND_ELEMENTS_SCRIPT: &str = r#"
+    function findElements(target) {
+        try {
+            return queryElements(target);
+        } catch (e) {
+            return [];
+        }
+    }
+
+    function queryElements(target) {
+        const roots = [document];
+
+        // 同一オリジンの iframe の document を追加（別オリジンは例外になるのでスキップ）
+        if (target.includeFrames) {
+            for (let i = 0; i < roots.length; i++) {
+                roots[i].querySelectorAll('iframe, frame').forEach(f => {
+                    try {
+                        if (f.contentDocument) roots.push(f.contentDocument);
+                    } catch (e) {}
+                });
+            }
+        }
+
+        // open な Shadow Root を追加
+        if (target.pierceShadow) {
+            for (let i = 0; i < roots.length; i++) {
+                roots[i].querySelectorAll('*').forEach(e => {
+                    if (e.shadowRoot) roots.push(e.shadowRoot);
+                });
+            }
+        }
+
+        const found = new Set();
+        for (const root of roots) {
+            const doc = root.ownerDocument || root;
+            if (target.kind === 'xpath') {
+                const snapshot = doc.evaluate(
+                    target.selector, root, null, XPathResult.ORDERED_NODE_SNAPSHOT_TYPE, null
+                );
+                for (let i = 0; i < snapshot.snapshotLength; i++) {
+                    const node = snapshot.snapshotItem(i);
+                    if (node.nodeType === Node.ELEMENT_NODE) found.add(node);
+                }
+            } else if (target.kind === 'text') {
+                const walker = doc.createTreeWalker(root, NodeFilter.SHOW_TEXT);
+                while (walker.nextNode()) {
+                    const node = walker.currentNode;
+                    if (node.nodeValue.includes(target.selector) && node.parentElement) {
+                        found.add(node.parentElement);
+                    }
+                }
+            } else {
+                root.querySelectorAll(target.selector).forEach(e => found.add(e));
+            }
+        }
+        return Array.from(found);
+    }
+"#;
+
+// セレクタが不正なら理由を返す
+async fn selector_error(driver: &WebDriver, target: &ElementTarget) -> Option<String> {
+    let script = format!(
+        r#"
+        {}
+        try {{
+            queryElements(arguments[0]);
+            return null;
+        }} catch (e) {{
+            return String(e);
+        }}
+        "#,
+        FIND_ELEMENTS_SCRIPT
+    );
+    match driver.execute(&script, vec![target.to_arg()]).await {
+        Ok(ret) => ret.json().as_str().map(str::to_string),
+        Err(e) => {
+            debug!("Failed to check selector: {}", e);
+            None
+        }
+    }
+}
+
+// 一致する要素がなかったときの警告（セレクタが不正ならその理由）
+pub async fn unmatched_warning(driver: &WebDriver, kind: &str, target: &ElementTarget) -> String {
+    match selector_error(driver, target).await {
+        Some(error) => format!(
+            "Invalid selector in {} rule: {} ({})",
+            kind, target.selector, error
+        ),
+        None => format!("No elements matched {} rule: {}", kind, target.selector),
+    }
+}
+
+// ドキュメント座標（CSS px）の矩形
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DocumentRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MaskRule {
+    #[serde(flatten)]
+    pub target: ElementTarget,
+    #[serde(default)]
+    pub effect: MaskEffect,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HighlightRule {
+    #[serde(flatten)]
+    pub target: ElementTarget,
+    #[serde(default = "default_highlight_color")]
+    pub color: String,
+    // 番号ラベルを付けるか
+    #[serde(default)]
+    pub label: bool,
+}
+
+fn default_highlight_color() -> String {
+    "#ff0000".to_string()
+}
+
+// 要素の矩形を、表示領域内に切り詰めたドキュメント座標に変換する関数
+// `clipToViewport` が false の場合は縦方向を切り詰めない（ページ全体を一度に撮影する場合）
+pub const DOCUMENT_RECTS_SCRIPT: &str = r#"
+    function visibleDocumentRect(rect, win, clipToViewport) {
+        let x = rect.left, y = rect.top;
+
+        // iframe 内の矩形はフレームの位置を足してトップレベルの座標にする
+        while (win && win.frameElement) {
+            const frame = win.frameElement.getBoundingClientRect();
+            x += frame.left + win.frameElement.clientLeft;
+            y += frame.top + win.frameElement.clientTop;
+            win = win.parent;
+        }
+
+        const left = Math.max(x, 0);
+        const top = clipToViewport ? Math.max(y, 0) : y;
+        const right = Math.min(x + rect.width, window.innerWidth);
+        const bottom = clipToViewport ? Math.min(y + rect.height, window.innerHeight) : y + rect.height;
+        if (right <= left || bottom <= top) return null;
+
+        return { x: left, y: top + window.scrollY, width: right - left, height: bottom - top };
+    }
+
+    function documentRects(elements, clipToViewport) {
+        return elements
+            .map(e => visibleDocumentRect(e.getBoundingClientRect(), e.ownerDocument.defaultView, clipToViewport))
+            .filter(r => r !== null);
+    }
+"#;
+
+// 要素の矩形をドキュメント座標で取得（`clip_to_viewport` なら現在見えている部分のみ）
+pub async fn get_element_rects(
+    driver: &WebDriver,
+    target: &ElementTarget,
+    clip_to_viewport: bool,
+) -> Result<Vec<DocumentRect>, Box<dyn Error>> {
+    debug!("get_element_rects: {:?}", target);
+    if target.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let script = format!(
+        r#"
+        {}
+        {}
+        return documentRects(findElements(arguments[0]), arguments[1]);
+        "#,
+        FIND_ELEMENTS_SCRIPT, DOCUMENT_RECTS_SCRIPT
+    );
+
+    let args = vec![target.to_arg(), json!(clip_to_viewport)];
+    let result = driver.execute(&script, args).await?;
+    let rects: Vec<DocumentRect> = serde_json::from_value(result.json().clone())?;
+    Ok(rects)
+}
+
+// よく使うテキストのパターン
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionPreset {
+    Email,
+    Phone,
+    CardNumber,
+}
+
+impl RedactionPreset {
+    // JavaScript の正規表現として渡すパターン
+    fn pattern(&self) -> &'static str {
+        match self {
+            RedactionPreset::Email => r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+            // 区切りあり、区切りなしの国際番号（合計 10〜13 桁）、区切りなしの `0` 始まり（10〜11 桁）
+            RedactionPreset::Phone => concat!(
+                r"(?:\+\d{1,3}[\s-]?)?\(?\d{1,4}\)?[\s-]\d{1,4}[\s-]\d{3,4}",
+                r"|\+\d{1,3}[\s-]?\d{9,10}\b",
+                r"|\b0\d{9,10}\b",
+            ),
+            RedactionPreset::CardNumber => r"\b(?:\d{4}[\s-]?){3}\d{1,7}\b",
+        }
+    }
+}
+
+// 正規表現に一致するテキストを塗りつぶす設定
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TextRedaction {
+    pub presets: Vec<RedactionPreset>,
+    // JavaScript の正規表現
+    pub patterns: Vec<String>,
+    pub effect: MaskEffect,
+}
+
+impl TextRedaction {
+    pub fn patterns(&self) -> Vec<String> {
+        self.presets
+            .iter()
+            .map(|p| p.pattern().to_string())
+            .chain(self.patterns.iter().filter(|p| !p.is_empty()).cloned())
+            .collect()
+    }
+}
 
-    if selectors.trim().is_empty() {
+// テキスト検索の結果
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TextMatches {
+    // ページ全体で描画されている一致の数
+    pub count: u64,
+    // 一致した範囲の矩形
+    pub rects: Vec<DocumentRect>,
+    pub invalid_patterns: Vec<String>,
+}
+
+// テキストノードを正規表現で検索し、一致した範囲の矩形をドキュメント座標で取得
+pub async fn find_text_matches(
+    driver: &WebDriver,
+    patterns: &[String],
+    clip_to_viewport: bool,
+) -> Result<TextMatches, Box<dyn Error>> {
+    debug!("find_text_matches: {:?}", patterns);
+    if patterns.is_empty() {
+        return Ok(TextMatches::default());
+    }
+
+    let script = format!(
+        r#"
+        {}
+        const regexps = [];
+        const invalidPatterns = [];
+        for (const pattern of arguments[0]) {{
+            try {{
+                regexps.push(new RegExp(pattern, 'g'));
+            }} catch (e) {{
+                invalidPatterns.push(pattern);
+            }}
+        }}
+
+        let count = 0;
+        const rects = [];
+        const walker = document.createTreeWalker(document.body, NodeFilter.SHOW_TEXT, {{
+            acceptNode: node => ['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE'].includes(node.parentNode.nodeName)
+                ? NodeFilter.FILTER_REJECT
+                : NodeFilter.FILTER_ACCEPT,
+        }});
+        while (walker.nextNode()) {{
+            const node = walker.currentNode;
+            for (const regexp of regexps) {{
+                for (const match of node.nodeValue.matchAll(regexp)) {{
+                    if (match[0].length === 0) continue;
+                    const range = document.createRange();
+                    range.setStart(node, match.index);
+                    range.setEnd(node, match.index + match[0].length);
+                    const clientRects = Array.from(range.getClientRects());
+                    if (clientRects.length === 0) continue;
+                    count++;
+                    clientRects
+                        .map(r => visibleDocumentRect(r, window, arguments[1]))
+                        .filter(r => r !== null)
+                        .forEach(r => rects.push(r));
+                }}
+            }}
+        }}
+        return {{ count, rects, invalidPatterns }};
+        "#,
+        DOCUMENT_RECTS_SCRIPT
+    );
+
+    let args = vec![json!(patterns), json!(clip_to_viewport)];
+    let result = driver.execute(&script, args).await?;
+    let matches: TextMatches = serde_json::from_value(result.json().clone())?;
+    Ok(matches)
+}
+
+// 指定した要素をルールの方法で非表示にし、一致した要素数を返す
+pub async fn hide_elements(driver: &WebDriver, rule: &HideRule) -> Result<u64, Box<dyn Error>> {
+    debug!("Hiding elements: {:?} ({:?})", rule.target, rule.method);
+
+    if rule.target.is_empty() {
         info!("No elements to hide.");
-        return Ok(());
+        return Ok(0);
     }
 
+    let (property, value) = rule.method.css();
     let script = format!(
         r#"
-        (function() {{
-            let elements = document.querySelectorAll("{}");
-            elements.forEach(e => e.style.visibility = 'hidden');
-        }})();
+        {}
+        const elements = findElements(arguments[0]);
+        elements.forEach(e => e.style.setProperty(arguments[1], arguments[2], 'important'));
+        return elements.length;
         "#,
-        selectors
+        FIND_ELEMENTS_SCRIPT
     );
 
-    driver.execute(&script, vec![]).await?;
-    info!("Elements hidden: {}", selectors);
-    Ok(())
+    let args = vec![rule.target.to_arg(), json!(property), json!(value)];
+    let count = driver
+        .execute(&script, args)
+        .await?
+        .json()
+        .as_u64()
+        .unwrap_or(0);
+    info!(
+        "Elements hidden: {} ({} matched)",
+        rule.target.selector, count
+    );
+    Ok(count)
 }
 
-// 指定した要素を元の状態に戻す（`display` プロパティをクリア）
-pub async fn show_elements(driver: &WebDriver, selectors: &str) -> Result<(), Box<dyn Error>> {
-    debug!("Restoring elements: {}", selectors);
-    if selectors.trim().is_empty() {
+// 指定した要素を元の状態に戻す（非表示に使ったプロパティをクリア）
+pub async fn show_elements(driver: &WebDriver, rule: &HideRule) -> Result<(), Box<dyn Error>> {
+    debug!("Restoring elements: {:?}", rule.target);
+    if rule.target.is_empty() {
         return Ok(());
     }
 
+    let (property, _) = rule.method.css();
     let script = format!(
         r#"
-        (function() {{
-            let elements = document.querySelectorAll("{}");
-            elements.forEach(e => e.style.visibility = '');
-        }})();
+        {}
+        findElements(arguments[0]).forEach(e => e.style.removeProperty(arguments[1]));
         "#,
-        selectors
+        FIND_ELEMENTS_SCRIPT
     );
 
-    driver.execute(&script, vec![]).await?;
-    info!("Elements restored: {}", selectors);
+    let args = vec![rule.target.to_arg(), json!(property)];
+    driver.execute(&script, args).await?;
+    info!("Elements restored: {}", rule.target.selector);
+    Ok(())
+}
+
+// ピクセル単位で比較できるようにページの動きを止める設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Determinism {
+    pub enabled: bool,
+    // `Math.random` のシード
+    pub seed: u32,
+    // `Date.now()` / `new Date()` が返す時刻（UNIX時間のミリ秒）
+    pub fixed_time: f64,
+}
+
+impl Default for Determinism {
+    fn default() -> Self {
+        Determinism {
+            enabled: false,
+            seed: 1,
+            fixed_time: 1_704_067_200_000.0, // 2024-01-01T00:00:00Z
+        }
+    }
+}
+
+// 時刻と乱数を固定する関数（一度だけ差し替える）
+const FREEZE_CLOCK_SCRIPT: &str = r#"
+    function freezeClock(seed, fixedTime) {
+        if (window.__scshokiFrozen) return;
+        window.__scshokiFrozen = true;
+
+        // `new` なしの `Date()` は文字列を返すため、クラスではなく関数で差し替える
+        const OriginalDate = Date;
+        function FixedDate(...args) {
+            if (!new.target) return new OriginalDate(fixedTime).toString();
+            return args.length === 0 ? new OriginalDate(fixedTime) : new OriginalDate(...args);
+        }
+        FixedDate.prototype = OriginalDate.prototype;
+        FixedDate.UTC = OriginalDate.UTC;
+        FixedDate.parse = OriginalDate.parse;
+        FixedDate.now = () => fixedTime;
+        window.Date = FixedDate;
+
+        // mulberry32
+        let state = seed >>> 0;
+        Math.random = function() {
+            state = (state + 0x6D2B79F5) >>> 0;
+            let t = state;
+            t = Math.imul(t ^ (t >>> 15), t | 1);
+            t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+            return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+        };
+    }
+"#;
+
+// ページのスクリプトより先に実行する、時刻と乱数を固定するスクリプト
+pub fn freeze_clock_script(determinism: &Determinism) -> String {
+    format!(
+        "{}\nfreezeClock({}, {});",
+        FREEZE_CLOCK_SCRIPT, determinism.seed, determinism.fixed_time
+    )
+}
+
+// Chrome で新しいドキュメントの読み込み前に実行するスクリプトを登録する（`goto` の前に呼ぶ）
+pub async fn add_init_script(driver: &WebDriver, source: &str) -> Result<(), Box<dyn Error>> {
+    ChromeDevTools::new(driver.handle.clone())
+        .execute_cdp_with_params(
+            "Page.addScriptToEvaluateOnNewDocument",
+            json!({ "source": source }),
+        )
+        .await?;
+    Ok(())
+}
+
+// アニメーション・トランジション・キャレット・動画を止め、時刻と乱数を固定する
+pub async fn freeze_page(
+    driver: &WebDriver,
+    determinism: &Determinism,
+) -> Result<(), Box<dyn Error>> {
+    debug!("freeze_page");
+    if !determinism.enabled {
+        return Ok(());
+    }
+
+    let script = r#"
+        const [seed, fixedTime] = arguments;
+        freezeClock(seed, fixedTime);
+
+        // CSS アニメーションは最終状態に飛ばし、トランジションとキャレットを無効化
+        if (!document.querySelector('style[data-scshoki-determinism]')) {
+            const style = document.createElement('style');
+            style.setAttribute('data-scshoki-determinism', '');
+            style.textContent = `
+                *, *::before, *::after {
+                    animation-duration: 0s !important;
+                    animation-delay: 0s !important;
+                    animation-iteration-count: 1 !important;
+                    transition: none !important;
+                    caret-color: transparent !important;
+                    scroll-behavior: auto !important;
+                }
+            `;
+            (document.head || document.documentElement).appendChild(style);
+        }
+
+        // Web Animations API のアニメーションも終了させる（無限ループは止めて先頭に戻す）
+        if (document.getAnimations) {
+            document.getAnimations().forEach(a => {
+                try {
+                    a.finish();
+                } catch (e) {
+                    a.pause();
+                    a.currentTime = 0;
+                }
+            });
+        }
+
+        // 動画・音声を止めてポスター（先頭フレーム）を表示
+        document.querySelectorAll('video, audio').forEach(media => {
+            media.autoplay = false;
+            media.removeAttribute('autoplay');
+            media.pause();
+            if (media.poster) {
+                media.load();
+            } else {
+                media.currentTime = 0;
+            }
+        });
+    "#;
+
+    driver
+        .execute(
+            &format!("{}{}", FREEZE_CLOCK_SCRIPT, script),
+            vec![json!(determinism.seed), json!(determinism.fixed_time)],
+        )
+        .await?;
+    info!("Page frozen for deterministic capture.");
+    Ok(())
+}
+
+// `<style>` 要素として CSS を注入（同じ `id` の要素があれば置き換える）
+pub async fn inject_css(driver: &WebDriver, id: &str, css: &str) -> Result<(), Box<dyn Error>> {
+    debug!("inject_css: {}", id);
+    let script = r#"
+        let style = document.querySelector(`style[data-scshoki-hook="${arguments[0]}"]`);
+        if (!style) {
+            style = document.createElement('style');
+            style.setAttribute('data-scshoki-hook', arguments[0]);
+            (document.head || document.documentElement).appendChild(style);
+        }
+        style.textContent = arguments[1];
+    "#;
+    driver.execute(script, vec![json!(id), json!(css)]).await?;
+    Ok(())
+}
+
+// ユーザー指定の JavaScript を実行
+pub async fn run_script(driver: &WebDriver, script: &str) -> Result<(), Box<dyn Error>> {
+    debug!("run_script");
+    driver.execute(script, vec![]).await?;
     Ok(())
 }
 
@@ -90,3 +655,76 @@ pub async fn scroll_by(driver: &WebDriver, pixels: f64) -> Result<(), Box<dyn Er
     driver.execute(&script, vec![]).await?;
     Ok(())
 }
+
+// 撮影時点の DOM とテキスト（後から検索できるように保存する）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomSnapshot {
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    #[serde(skip_serializing)]
+    pub html: String,
+    // 画面に表示されているテキスト（`innerText` なので非表示の要素は含まない）
+    #[serde(skip_serializing)]
+    pub text: String,
+}
+
+pub async fn get_dom_snapshot(driver: &WebDriver) -> Result<DomSnapshot, Box<dyn Error>> {
+    debug!("get_dom_snapshot");
+    let script = r#"
+        const doctype = document.doctype ? new XMLSerializer().serializeToString(document.doctype) + "\n" : "";
+        const meta = document.querySelector('meta[name="description" i]');
+        return {
+            url: location.href,
+            title: document.title,
+            description: meta ? meta.getAttribute("content") || "" : "",
+            html: doctype + document.documentElement.outerHTML,
+            text: document.body ? document.body.innerText : "",
+        };
+    "#;
+    let result = driver.execute(script, vec![]).await?;
+    Ok(serde_json::from_value(result.json().clone())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    fn phone_matches(text: &str) -> Vec<String> {
+        let pattern = Regex::new(RedactionPreset::Phone.pattern()).unwrap();
+        pattern
+            .find_iter(text)
+            .map(|m| m.as_str().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn phone_matches_numbers_with_and_without_separators() {
+        for number in [
+            "090-1234-5678",
+            "03 1234 5678",
+            "+81 90-1234-5678",
+            "09012345678",
+            "0312345678",
+            "+819012345678",
+            "+81 9012345678",
+        ] {
+            assert_eq!(phone_matches(&format!("TEL: {}", number)), [number]);
+        }
+    }
+
+    #[test]
+    fn phone_ignores_dates_and_ids() {
+        for text in [
+            "2024-01-01",
+            "20240101",
+            "1234567890",
+            "ID 123456789012",
+            "012345678901",
+        ] {
+            assert!(phone_matches(text).is_empty(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use log::debug;
+use serde::Deserialize;
+use serde_json::json;
+use std::error::Error;
+use thirtyfour::prelude::*;
+
+use crate::config::constants::DEVICE_OS;
+use crate::services::dom::scroll_by;
+use crate::services::webrdiver::SessionCommand;
+
+// スクロールの方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollDriver {
+    // `window.scrollBy`
+    #[default]
+    Script,
+    // W3C Actions のタッチ操作でスワイプ
+    Touch,
+    // Appium の `mobile: scroll` / `mobile: scrollGesture`
+    MobileScroll,
+}
+
+const SWIPE_DISTANCE_RATIO: f64 = 0.8; // 1回のスワイプで動かす画面の割合
+const SWIPE_DURATION_MS: u64 = 600;
+const SWIPE_HOLD_MS: u64 = 300; // 慣性スクロールを防ぐため、離す前に止める時間
+
+// 1画面分（タッチ操作では約8割）下にスクロールする
+// 実際のスクロール量は呼び出し側で `window.scrollY` を取得して確認すること
+pub async fn scroll_page(
+    driver: &WebDriver,
+    scroll_driver: ScrollDriver,
+    inner_width: f64,
+    inner_height: f64,
+) -> Result<(), Box<dyn Error>> {
+    debug!("scroll_page: {:?}", scroll_driver);
+
+    match scroll_driver {
+        ScrollDriver::Script => scroll_by(driver, inner_height).await,
+        ScrollDriver::Touch => swipe_up(driver, inner_width, inner_height).await,
+        ScrollDriver::MobileScroll => mobile_scroll(driver).await,
+    }
+}
+
+// 指で下から上にスワイプ（座標は表示領域の CSS px）
+async fn swipe_up(
+    driver: &WebDriver,
+    inner_width: f64,
+    inner_height: f64,
+) -> Result<(), Box<dyn Error>> {
+    let x = (inner_width / 2.0) as i64;
+    let start_y = (inner_height * 0.9) as i64;
+    let end_y = start_y - (inner_height * SWIPE_DISTANCE_RATIO) as i64;
+
+    let actions = json!({
+        "actions": [{
+            "type": "pointer",
+            "id": "finger1",
+            "parameters": { "pointerType": "touch" },
+            "actions": [
+                { "type": "pointerMove", "duration": 0, "origin": "viewport", "x": x, "y": start_y },
+                { "type": "pointerDown", "button": 0 },
+                { "type": "pause", "duration": 100 },
+                { "type": "pointerMove", "duration": SWIPE_DURATION_MS, "origin": "viewport", "x": x, "y": end_y },
+                { "type": "pause", "duration": SWIPE_HOLD_MS },
+                { "type": "pointerUp", "button": 0 },
+            ],
+        }],
+    });
+
+    SessionCommand::post("actions", actions)
+        .send(driver)
+        .await?;
+    SessionCommand::delete("actions").send(driver).await?; // 入力状態を解放
+    Ok(())
+}
+
+// Appium のネイティブなスクロールコマンド
+async fn mobile_scroll(driver: &WebDriver) -> Result<(), Box<dyn Error>> {
+    let os = DEVICE_OS.lock().unwrap().clone().unwrap_or_default();
+
+    if os == "Android" {
+        // スクロールする領域は WebView の矩形（デバイスの px）
+        let rect = driver
+            .execute("mobile: viewportRect", vec![])
+            .await?
+            .json()
+            .clone();
+        driver
+            .execute(
+                "mobile: scrollGesture",
+                vec![json!({
+                    "left": rect["left"],
+                    "top": rect["top"],
+                    "width": rect["width"],
+                    "height": rect["height"],
+                    "direction": "down",
+                    "percent": SWIPE_DISTANCE_RATIO,
+                })],
+            )
+            .await?;
+    } else {
+        driver
+            .execute("mobile: scroll", vec![json!({ "direction": "down" })])
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,337 @@
+use chrono::{DateTime, SecondsFormat};
+use log::info;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use thirtyfour::prelude::*;
+
+use crate::services::webrdiver::SessionCommand;
+
+// Resource Timing API から通信の一覧を取得（ヘッダーなどは取れない）
+// `Timing-Allow-Origin` のない別オリジンはサイズが 0 になるため、不明（-1）とする
+const RESOURCE_TIMING_SCRIPT: &str = r#"
+    const origin = performance.timeOrigin || performance.timing.navigationStart;
+    const span = (start, end) => (start > 0 && end >= start ? end - start : -1);
+    return performance.getEntriesByType("navigation")
+        .concat(performance.getEntriesByType("resource"))
+        .map(e => ({
+            url: e.name,
+            type: e.initiatorType,
+            start: origin + e.startTime,
+            duration: e.duration,
+            status: e.responseStatus || 0,
+            protocol: e.nextHopProtocol || "",
+            transferSize: e.transferSize || -1,
+            bodySize: e.encodedBodySize || -1,
+            contentSize: e.decodedBodySize || -1,
+            dns: span(e.domainLookupStart, e.domainLookupEnd),
+            connect: span(e.connectStart, e.connectEnd),
+            ssl: e.secureConnectionStart > 0 ? span(e.secureConnectionStart, e.connectEnd) : -1,
+            wait: span(e.requestStart, e.responseStart),
+            receive: span(e.responseStart, e.responseEnd),
+        }));
+"#;
+
+// HAR の1件分にまとめる前の通信
+#[derive(Debug, Default)]
+struct NetworkRequest {
+    url: String,
+    method: String,
+    // エポックからのミリ秒
+    started_ms: f64,
+    time_ms: f64,
+    status: i64,
+    status_text: String,
+    protocol: String,
+    mime_type: String,
+    // リダイレクトした場合の移動先
+    redirect_url: String,
+    request_headers: Value,
+    response_headers: Value,
+    transfer_size: i64,
+    body_size: i64,
+    content_size: i64,
+    timings: Timings,
+    error: Option<String>,
+}
+
+// HAR の `timings`（ミリ秒、不明なら -1）
+#[derive(Debug)]
+struct Timings {
+    dns: f64,
+    connect: f64,
+    ssl: f64,
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Timings {
+            dns: -1.0,
+            connect: -1.0,
+            ssl: -1.0,
+            send: 0.0,
+            wait: 0.0,
+            receive: 0.0,
+        }
+    }
+}
+
+// `goog:loggingPrefs` の performance ログ（CDP のイベント）を読む
+// 読み出したログは消えるため、一度だけ呼んで使い回す
+pub async fn read_performance_log(driver: &WebDriver) -> Result<Vec<Value>, String> {
+    let logs = SessionCommand::post("se/log", json!({ "type": "performance" }))
+        .send(driver)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(logs
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|log| log["message"].as_str())
+        .filter_map(|message| serde_json::from_str::<Value>(message).ok())
+        .map(|message| message["message"].clone())
+        .collect())
+}
+
+// 通信の一覧を HAR 1.2 の形式で返す
+// Chrome はパフォーマンスログ（CDP の Network イベント）、それ以外は Resource Timing API から作る
+// Resource Timing API では取れないものは警告として返す
+pub async fn collect_network_log(
+    driver: &WebDriver,
+    performance_log: &[Value],
+) -> Result<(Value, Vec<String>), String> {
+    let mut warnings = vec![];
+    let mut requests = performance_log_requests(performance_log);
+    if requests.is_empty() {
+        requests = resource_timing_requests(driver).await?;
+        warnings.push(
+            "The network log is built from the Resource Timing API: failed and blocked requests \
+             are missing, and cross-origin sizes and receive times are unknown"
+                .to_string(),
+        );
+    }
+    requests.sort_by(|a, b| a.started_ms.total_cmp(&b.started_ms));
+    info!("Collected {} network requests.", requests.len());
+
+    let title = driver.title().await.unwrap_or_default();
+    let started = requests.first().map(|r| r.started_ms).unwrap_or_default();
+
+    let har = json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "scshoki", "version": env!("CARGO_PKG_VERSION") },
+            "pages": [{
+                "startedDateTime": to_iso8601(started),
+                "id": "page_1",
+                "title": title,
+                "pageTimings": {},
+            }],
+            "entries": requests.iter().map(to_har_entry).collect::<Vec<_>>(),
+        }
+    });
+    Ok((har, warnings))
+}
+
+async fn resource_timing_requests(driver: &WebDriver) -> Result<Vec<NetworkRequest>, String> {
+    let entries = driver
+        .execute(RESOURCE_TIMING_SCRIPT, vec![])
+        .await
+        .map_err(|e| format!("Failed to read resource timing: {}", e))?
+        .json()
+        .clone();
+
+    Ok(entries
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|e| NetworkRequest {
+            url: e["url"].as_str().unwrap_or_default().to_string(),
+            method: "GET".to_string(),
+            started_ms: e["start"].as_f64().unwrap_or_default(),
+            time_ms: e["duration"].as_f64().unwrap_or_default(),
+            status: e["status"].as_i64().unwrap_or_default(),
+            protocol: e["protocol"].as_str().unwrap_or_default().to_string(),
+            mime_type: e["type"].as_str().unwrap_or_default().to_string(),
+            transfer_size: e["transferSize"].as_i64().unwrap_or(-1),
+            body_size: e["bodySize"].as_i64().unwrap_or(-1),
+            content_size: e["contentSize"].as_i64().unwrap_or(-1),
+            timings: Timings {
+                dns: e["dns"].as_f64().unwrap_or(-1.0),
+                connect: e["connect"].as_f64().unwrap_or(-1.0),
+                ssl: e["ssl"].as_f64().unwrap_or(-1.0),
+                send: 0.0,
+                wait: e["wait"].as_f64().unwrap_or_default().max(0.0),
+                receive: e["receive"].as_f64().unwrap_or_default().max(0.0),
+            },
+            ..NetworkRequest::default()
+        })
+        .collect())
+}
+
+// パフォーマンスログの Network イベントから通信を集める
+fn performance_log_requests(performance_log: &[Value]) -> Vec<NetworkRequest> {
+    let mut requests: HashMap<String, NetworkRequest> = HashMap::new();
+    // リダイレクトは同じ requestId で続くため、3xx の応答は先に取り出しておく
+    let mut redirects = vec![];
+    // CDP の timestamp（秒、単調増加）とエポックの差
+    let mut wall_offset: HashMap<String, f64> = HashMap::new();
+
+    for message in performance_log {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let Some(id) = params["requestId"].as_str() else {
+            continue;
+        };
+        let timestamp = params["timestamp"].as_f64().unwrap_or_default();
+
+        match method {
+            "Network.requestWillBeSent" => {
+                let wall_time = params["wallTime"].as_f64().unwrap_or_default();
+                let request = &params["request"];
+                if let Some(mut entry) = requests.remove(id) {
+                    if params["redirectResponse"].is_object() {
+                        let offset = wall_offset.get(id).copied().unwrap_or_default();
+                        entry.time_ms = ((timestamp + offset) * 1000.0 - entry.started_ms).max(0.0);
+                        entry.redirect_url =
+                            request["url"].as_str().unwrap_or_default().to_string();
+                        apply_response(&mut entry, &params["redirectResponse"]);
+                        redirects.push(entry);
+                    }
+                }
+                wall_offset.insert(id.to_string(), wall_time - timestamp);
+                requests.insert(
+                    id.to_string(),
+                    NetworkRequest {
+                        url: request["url"].as_str().unwrap_or_default().to_string(),
+                        method: request["method"].as_str().unwrap_or("GET").to_string(),
+                        started_ms: wall_time * 1000.0,
+                        request_headers: request["headers"].clone(),
+                        transfer_size: -1,
+                        body_size: -1,
+                        ..NetworkRequest::default()
+                    },
+                );
+            }
+            "Network.responseReceived" => {
+                if let Some(entry) = requests.get_mut(id) {
+                    apply_response(entry, &params["response"]);
+                }
+            }
+            "Network.loadingFinished" | "Network.loadingFailed" => {
+                if let Some(entry) = requests.get_mut(id) {
+                    let offset = wall_offset.get(id).copied().unwrap_or_default();
+                    entry.time_ms = ((timestamp + offset) * 1000.0 - entry.started_ms).max(0.0);
+                    if let Some(size) = params["encodedDataLength"].as_f64() {
+                        entry.transfer_size = size as i64;
+                    }
+                    entry.error = params["errorText"].as_str().map(str::to_string);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    requests.into_values().chain(redirects).collect()
+}
+
+// CDP の `Response` を通信に反映
+fn apply_response(entry: &mut NetworkRequest, response: &Value) {
+    entry.status = response["status"].as_i64().unwrap_or_default();
+    entry.status_text = response["statusText"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    entry.protocol = response["protocol"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    entry.mime_type = response["mimeType"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    entry.response_headers = response["headers"].clone();
+    entry.timings = cdp_timings(&response["timing"]);
+}
+
+// CDP の `ResourceTiming`（requestTime からのミリ秒、未使用は -1）を HAR の区間に変換
+fn cdp_timings(timing: &Value) -> Timings {
+    let get = |key: &str| timing[key].as_f64().unwrap_or(-1.0);
+    let span = |start: f64, end: f64| {
+        if start >= 0.0 && end >= start {
+            end - start
+        } else {
+            -1.0
+        }
+    };
+
+    Timings {
+        dns: span(get("dnsStart"), get("dnsEnd")),
+        connect: span(get("connectStart"), get("connectEnd")),
+        ssl: span(get("sslStart"), get("sslEnd")),
+        send: span(get("sendStart"), get("sendEnd")).max(0.0),
+        wait: span(get("sendEnd"), get("receiveHeadersEnd")).max(0.0),
+        receive: 0.0,
+    }
+}
+
+fn to_har_entry(request: &NetworkRequest) -> Value {
+    let t = &request.timings;
+    let mut entry = json!({
+        "startedDateTime": to_iso8601(request.started_ms),
+        "time": request.time_ms,
+        "request": {
+            "method": request.method,
+            "url": request.url,
+            "httpVersion": request.protocol,
+            "cookies": [],
+            "headers": to_har_headers(&request.request_headers),
+            "queryString": [],
+            "headersSize": -1,
+            "bodySize": -1,
+        },
+        "response": {
+            "status": request.status,
+            "statusText": request.status_text,
+            "httpVersion": request.protocol,
+            "cookies": [],
+            "headers": to_har_headers(&request.response_headers),
+            "content": { "size": request.content_size, "mimeType": request.mime_type },
+            "redirectURL": request.redirect_url,
+            "headersSize": -1,
+            "bodySize": request.body_size,
+            "_transferSize": request.transfer_size,
+        },
+        "cache": {},
+        "timings": {
+            "blocked": -1,
+            "dns": t.dns,
+            "connect": t.connect,
+            "ssl": t.ssl,
+            "send": t.send,
+            "wait": t.wait,
+            "receive": t.receive,
+        },
+    });
+    if let Some(error) = &request.error {
+        entry["_error"] = json!(error);
+    }
+    entry
+}
+
+fn to_har_headers(headers: &Value) -> Vec<Value> {
+    headers
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, value)| json!({ "name": name, "value": value.as_str().unwrap_or_default() }))
+        .collect()
+}
+
+fn to_iso8601(epoch_ms: f64) -> String {
+    DateTime::from_timestamp_millis(epoch_ms as i64)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Millis, true)
+}
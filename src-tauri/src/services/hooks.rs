@@ -0,0 +1,103 @@
+use log::{debug, info};
+use serde::Deserialize;
+use std::fs;
+use thirtyfour::prelude::*;
+
+use crate::config::constants::HOOKS_DIR;
+use crate::services::dom::{inject_css, run_script};
+
+// 注入するタイミング
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookPoint {
+    // ページ読み込み完了後
+    AfterLoad,
+    // 最初のスクリーンショットの前
+    BeforeFirstTile,
+    // 各スクリーンショットの前
+    BeforeEachTile,
+}
+
+impl HookPoint {
+    const ALL: [HookPoint; 3] = [
+        HookPoint::AfterLoad,
+        HookPoint::BeforeFirstTile,
+        HookPoint::BeforeEachTile,
+    ];
+
+    // ドメインごとのファイル名にも使う
+    fn name(&self) -> &'static str {
+        match self {
+            HookPoint::AfterLoad => "after_load",
+            HookPoint::BeforeFirstTile => "before_first_tile",
+            HookPoint::BeforeEachTile => "before_each_tile",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InjectionHook {
+    pub point: HookPoint,
+    #[serde(default)]
+    pub css: Option<String>,
+    #[serde(default)]
+    pub js: Option<String>,
+}
+
+// `~/.scshoki/hooks/<host>/<point>.css|js` からドメインごとのフックを読み込む
+pub fn load_domain_hooks(url: &str) -> Vec<InjectionHook> {
+    let Some(host) = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+    else {
+        return vec![];
+    };
+
+    let dir = HOOKS_DIR.join(&host);
+    if !dir.is_dir() {
+        debug!("No hooks for {}", host);
+        return vec![];
+    }
+
+    let read = |point: HookPoint, ext: &str| {
+        let path = dir.join(format!("{}.{}", point.name(), ext));
+        fs::read_to_string(&path).ok().inspect(|_| {
+            info!("Loaded hook: {:?}", path);
+        })
+    };
+
+    HookPoint::ALL
+        .iter()
+        .map(|&point| InjectionHook {
+            point,
+            css: read(point, "css"),
+            js: read(point, "js"),
+        })
+        .filter(|hook| hook.css.is_some() || hook.js.is_some())
+        .collect()
+}
+
+// 指定したタイミングのフックを実行し、失敗したものは警告として返す
+pub async fn run_hooks(
+    driver: &WebDriver,
+    hooks: &[InjectionHook],
+    point: HookPoint,
+) -> Vec<String> {
+    let mut warnings = vec![];
+
+    for (index, hook) in hooks.iter().enumerate().filter(|(_, h)| h.point == point) {
+        if let Some(css) = &hook.css {
+            let id = format!("{}-{}", point.name(), index);
+            if let Err(e) = inject_css(driver, &id, css).await {
+                warnings.push(format!("Failed to inject CSS ({}): {}", point.name(), e));
+            }
+        }
+        if let Some(js) = &hook.js {
+            if let Err(e) = run_script(driver, js).await {
+                warnings.push(format!("Failed to run script ({}): {}", point.name(), e));
+            }
+        }
+    }
+
+    warnings
+}
@@ -1,7 +1,40 @@
-use image::GenericImageView;
+use image::imageops::{self, FilterType};
+use image::{GenericImageView, Rgba, RgbaImage};
 use log::debug;
+use serde::Deserialize;
 
 use crate::config::constants::DEVICE_DENSITY;
+use crate::services::dom::DocumentRect;
+
+const THUMBNAIL_SIZE: u32 = 32;
+const UNIFORM_TOLERANCE: u8 = 2;
+
+// マスクの塗り方
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MaskEffect {
+    // 単色で塗りつぶす（`#rrggbb` または `#rrggbbaa`）
+    Fill { color: String },
+    // モザイク（`block_size` はCSS px）
+    Pixelate { block_size: f64 },
+    // ガウスぼかし
+    Blur { sigma: f32 },
+}
+
+impl Default for MaskEffect {
+    fn default() -> Self {
+        MaskEffect::Fill {
+            color: "#000000".to_string(),
+        }
+    }
+}
+
+// 結合後の画像に適用するマスク
+#[derive(Debug, Clone)]
+pub struct MaskRegion {
+    pub rect: DocumentRect,
+    pub effect: MaskEffect,
+}
 
 // innerHieght分の高さでtrimして、画像の下の余白をカットする関数
 pub fn trim_extra_space(image_data: &[u8], inner_height: f64) -> Result<Vec<u8>, String> {
@@ -29,6 +62,73 @@ pub fn trim_extra_space(image_data: &[u8], inner_height: f64) -> Result<Vec<u8>,
     Ok(output.into_inner())
 }
 
+// 端末のスクリーンショットから WebView の矩形（デバイスの px）を切り出す
+pub fn crop_to_rect(
+    image_data: &[u8],
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, String> {
+    debug!("crop_to_rect");
+
+    let image =
+        image::load_from_memory(image_data).map_err(|e| format!("Failed to load image: {}", e))?;
+    let (image_width, image_height) = image.dimensions();
+    if left + width > image_width || top + height > image_height {
+        return Err(format!(
+            "Content rect {}x{}+{}+{} is outside the {}x{} screenshot",
+            width, height, left, top, image_width, image_height
+        ));
+    }
+
+    let cropped_image = image.crop_imm(left, top, width, height);
+
+    let mut output = std::io::Cursor::new(Vec::new());
+    cropped_image
+        .write_to(&mut output, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to save cropped image: {}", e))?;
+
+    Ok(output.into_inner())
+}
+
+// タイルの検証用に縮小した画像
+pub fn tile_thumbnail(image_data: &[u8]) -> Result<RgbaImage, String> {
+    let image =
+        image::load_from_memory(image_data).map_err(|e| format!("Failed to load image: {}", e))?;
+    Ok(imageops::resize(
+        &image,
+        THUMBNAIL_SIZE,
+        THUMBNAIL_SIZE,
+        FilterType::Triangle,
+    ))
+}
+
+// 全体が1色（白一色など）かどうか
+pub fn is_uniform_color(thumbnail: &RgbaImage) -> bool {
+    let Some(first) = thumbnail.pixels().next() else {
+        return true;
+    };
+    thumbnail.pixels().all(|pixel| {
+        pixel
+            .0
+            .iter()
+            .zip(first.0.iter())
+            .all(|(a, b)| a.abs_diff(*b) <= UNIFORM_TOLERANCE)
+    })
+}
+
+// 2枚の縮小画像の平均の差（チャンネルごと、0〜255）
+pub fn mean_difference(a: &RgbaImage, b: &RgbaImage) -> f64 {
+    let total: u64 = a
+        .as_raw()
+        .iter()
+        .zip(b.as_raw().iter())
+        .map(|(x, y)| x.abs_diff(*y) as u64)
+        .sum();
+    total as f64 / a.as_raw().len().max(1) as f64
+}
+
 // 最後のスクロールで、被った部分をカットする関数
 pub fn cut_scroll_overlap(
     image_data: &[u8],
@@ -60,3 +160,221 @@ pub fn cut_scroll_overlap(
 
     Ok(output.into_inner())
 }
+
+// 結合後の画像に描画する枠線
+#[derive(Debug, Clone)]
+pub struct HighlightRegion {
+    pub rect: DocumentRect,
+    pub color: String,
+    // 番号ラベルを付けるか
+    pub label: bool,
+}
+
+// 3x5 ドットの数字フォント（各行の下位3ビットを使用）
+const DIGIT_FONT: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const HIGHLIGHT_BORDER_WIDTH: f64 = 3.0; // 枠線の太さ（CSS px）
+const LABEL_DOT_SIZE: f64 = 3.0; // ラベルの数字1ドットの大きさ（CSS px）
+
+// 結合後の画像に枠線と番号ラベルを描画する関数（上から順に番号を振る）
+pub fn draw_highlights(image_data: &[u8], regions: &[HighlightRegion]) -> Result<Vec<u8>, String> {
+    debug!("draw_highlights: {} regions", regions.len());
+
+    let mut sorted: Vec<&HighlightRegion> = regions.iter().collect();
+    sorted.sort_by(|a, b| {
+        (a.rect.y, a.rect.x)
+            .partial_cmp(&(b.rect.y, b.rect.x))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    draw_outlines(image_data, &sorted)
+}
+
+// 枠線を描画し、ラベル付きのものに渡された順で番号を振る
+pub fn draw_outlines(image_data: &[u8], regions: &[&HighlightRegion]) -> Result<Vec<u8>, String> {
+    let mut image = image::load_from_memory(image_data)
+        .map_err(|e| format!("Failed to load image: {}", e))?
+        .to_rgba8();
+
+    let physical_density = DEVICE_DENSITY.lock().unwrap().unwrap_or(1.0); // デフォルト1.0
+    let border = ((HIGHLIGHT_BORDER_WIDTH * physical_density) as u32).max(1);
+    let dot = ((LABEL_DOT_SIZE * physical_density) as u32).max(1);
+
+    let mut number = 0;
+    for region in regions {
+        let Some((x, y, width, height)) = to_image_rect(&image, &region.rect, physical_density)
+        else {
+            continue;
+        };
+        let color = parse_hex_color(&region.color)?;
+
+        // 枠線（上下左右の4本）
+        let horizontal = RgbaImage::from_pixel(width, border.min(height), color);
+        let vertical = RgbaImage::from_pixel(border.min(width), height, color);
+        imageops::overlay(&mut image, &horizontal, x as i64, y as i64);
+        imageops::overlay(
+            &mut image,
+            &horizontal,
+            x as i64,
+            (y + height) as i64 - border as i64,
+        );
+        imageops::overlay(&mut image, &vertical, x as i64, y as i64);
+        imageops::overlay(
+            &mut image,
+            &vertical,
+            (x + width) as i64 - border as i64,
+            y as i64,
+        );
+
+        if region.label {
+            number += 1;
+            draw_number_label(&mut image, number, x, y, dot, color);
+        }
+    }
+
+    let mut output = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(&mut output, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to save highlighted image: {}", e))?;
+
+    Ok(output.into_inner())
+}
+
+// 枠の左上に番号ラベルを描画（上に余白があれば枠の外側に置く）
+fn draw_number_label(
+    image: &mut RgbaImage,
+    number: u32,
+    x: u32,
+    y: u32,
+    dot: u32,
+    color: Rgba<u8>,
+) {
+    let digits: Vec<usize> = number
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| d as usize)
+        .collect();
+
+    // 数字の間と周囲に1ドットずつ余白を取る
+    let label_width = (digits.len() as u32 * 4 + 1) * dot;
+    let label_height = 7 * dot;
+    let label_y = if y >= label_height {
+        y - label_height
+    } else {
+        y
+    };
+
+    let background = RgbaImage::from_pixel(label_width, label_height, color);
+    imageops::overlay(image, &background, x as i64, label_y as i64);
+
+    let white = Rgba([255, 255, 255, 255]);
+    for (i, digit) in digits.iter().enumerate() {
+        let digit_x = x + (i as u32 * 4 + 1) * dot;
+        for (row, bits) in DIGIT_FONT[*digit].iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                let dot_image = RgbaImage::from_pixel(dot, dot, white);
+                let dot_x = digit_x + col * dot;
+                let dot_y = label_y + (row as u32 + 1) * dot;
+                imageops::overlay(image, &dot_image, dot_x as i64, dot_y as i64);
+            }
+        }
+    }
+}
+
+// 結合後の画像にマスクを適用する関数（矩形はドキュメント座標なので密度を掛けて変換）
+pub fn apply_masks(image_data: &[u8], regions: &[MaskRegion]) -> Result<Vec<u8>, String> {
+    debug!("apply_masks: {} regions", regions.len());
+
+    let mut image = image::load_from_memory(image_data)
+        .map_err(|e| format!("Failed to load image: {}", e))?
+        .to_rgba8();
+
+    let physical_density = DEVICE_DENSITY.lock().unwrap().unwrap_or(1.0); // デフォルト1.0
+
+    for region in regions {
+        let Some((x, y, width, height)) = to_image_rect(&image, &region.rect, physical_density)
+        else {
+            continue;
+        };
+
+        match &region.effect {
+            MaskEffect::Fill { color } => {
+                let color = parse_hex_color(color)?;
+                let fill = RgbaImage::from_pixel(width, height, color);
+                imageops::overlay(&mut image, &fill, x as i64, y as i64);
+            }
+            MaskEffect::Pixelate { block_size } => {
+                let block = ((block_size * physical_density) as u32).max(1);
+                let area = imageops::crop_imm(&image, x, y, width, height).to_image();
+                let small = imageops::resize(
+                    &area,
+                    (width / block).max(1),
+                    (height / block).max(1),
+                    FilterType::Triangle,
+                );
+                let pixelated = imageops::resize(&small, width, height, FilterType::Nearest);
+                imageops::replace(&mut image, &pixelated, x as i64, y as i64);
+            }
+            MaskEffect::Blur { sigma } => {
+                let area = imageops::crop_imm(&image, x, y, width, height).to_image();
+                let blurred = imageops::blur(&area, sigma * physical_density as f32);
+                imageops::replace(&mut image, &blurred, x as i64, y as i64);
+            }
+        }
+    }
+
+    let mut output = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(&mut output, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to save masked image: {}", e))?;
+
+    Ok(output.into_inner())
+}
+
+// ドキュメント座標の矩形を画像のピクセル座標に変換し、画像内に収める
+fn to_image_rect(
+    image: &RgbaImage,
+    rect: &DocumentRect,
+    physical_density: f64,
+) -> Option<(u32, u32, u32, u32)> {
+    let (image_width, image_height) = image.dimensions();
+    let left = (rect.x * physical_density).floor().max(0.0) as u32;
+    let top = (rect.y * physical_density).floor().max(0.0) as u32;
+    let right = (((rect.x + rect.width) * physical_density).ceil() as u32).min(image_width);
+    let bottom = (((rect.y + rect.height) * physical_density).ceil() as u32).min(image_height);
+
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some((left, top, right - left, bottom - top))
+}
+
+// `#rrggbb` / `#rrggbbaa` 形式の色をパース
+pub fn parse_hex_color(color: &str) -> Result<Rgba<u8>, String> {
+    let hex = color.trim().trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .ok_or_else(|| format!("Invalid color: {}", color))
+    };
+
+    match hex.len() {
+        6 => Ok(Rgba([channel(0)?, channel(2)?, channel(4)?, 255])),
+        8 => Ok(Rgba([channel(0)?, channel(2)?, channel(4)?, channel(6)?])),
+        _ => Err(format!("Invalid color: {}", color)),
+    }
+}
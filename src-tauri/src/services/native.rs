@@ -0,0 +1,297 @@
+use image::{DynamicImage, GrayImage};
+use log::{debug, info};
+use serde::Deserialize;
+use std::fs;
+use thirtyfour::prelude::*;
+use tokio::time::{sleep, Duration};
+
+use crate::config::constants::SCREENSHOT_DIR;
+use crate::services::gesture::{scroll_page, ScrollDriver};
+use crate::services::screenshot::{CaptureMethod, FullPageCapture};
+
+// スワイプ後、慣性スクロールが止まるまで待つ時間
+const SWIPE_SETTLE: Duration = Duration::from_millis(800);
+// 行の平均の差がこれ未満なら同じ行とみなす
+const SAME_ROW_DIFFERENCE: f64 = 1.0;
+// 重なりの候補のうち、平均の差がこれ以上なら重なりが見つからなかったとみなす
+const OVERLAP_MAX_DIFFERENCE: f64 = 8.0;
+// 重なりの比較に使う行数（計算量を抑えるため間引く）
+const OVERLAP_SAMPLE_ROWS: u32 = 60;
+
+// 撮影するアプリ（未指定なら前面のアプリ）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AppTarget {
+    // Android
+    pub app_package: Option<String>,
+    pub app_activity: Option<String>,
+    // iOS
+    pub bundle_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NativeCaptureOptions {
+    #[serde(flatten)]
+    pub app: AppTarget,
+    pub android_serial: Option<String>,
+    pub max_swipes: u32,
+}
+
+impl Default for NativeCaptureOptions {
+    fn default() -> Self {
+        NativeCaptureOptions {
+            app: AppTarget::default(),
+            android_serial: None,
+            max_swipes: 20,
+        }
+    }
+}
+
+// ネイティブ画面をスワイプしながら撮影し、重なりを画像から検出して帯に分ける
+// （DOM がないので、前の画面と比べてどれだけ動いたかを画像から求める）
+pub async fn capture_native_screen(
+    driver: &WebDriver,
+    options: &NativeCaptureOptions,
+) -> Result<FullPageCapture, String> {
+    info!("Capturing native screen...");
+    let mut capture = FullPageCapture::default();
+
+    if !SCREENSHOT_DIR.exists() {
+        info!("Creating screenshots directory...");
+        fs::create_dir(&*SCREENSHOT_DIR)
+            .map_err(|e| format!("Failed to create screenshots directory: {}", e))?;
+    }
+
+    let window = driver
+        .get_window_rect()
+        .await
+        .map_err(|e| format!("Failed to get window size: {}", e))?;
+
+    let mut previous = take_device_screenshot(driver, 1).await?;
+    let mut stitcher = NativeStitcher::new(&previous);
+
+    for index in 2..=options.max_swipes + 1 {
+        scroll_page(
+            driver,
+            ScrollDriver::Touch,
+            window.width as f64,
+            window.height as f64,
+        )
+        .await
+        .map_err(|e| format!("Failed to swipe: {}", e))?;
+        sleep(SWIPE_SETTLE).await;
+
+        let current = take_device_screenshot(driver, index).await?;
+        let Some(layout) = find_scroll_layout(&previous, &current) else {
+            info!("Screen did not move. Reached the end.");
+            break;
+        };
+        debug!("Native scroll layout: {:?}", layout);
+
+        if !layout.overlap_found {
+            capture.warn(format!(
+                "No overlap found between native_{} and native_{}; appended whole view",
+                index - 1,
+                index
+            ));
+        }
+        stitcher.push(&current, &layout);
+        previous = current;
+
+        if index == options.max_swipes + 1 {
+            capture.warn(format!(
+                "Stopped after {} swipes; the screen may continue.",
+                options.max_swipes
+            ));
+        }
+    }
+
+    capture.screenshots = stitcher.finish(&previous)?;
+    capture.method = CaptureMethod::Stitched;
+    Ok(capture)
+}
+
+async fn take_device_screenshot(driver: &WebDriver, index: u32) -> Result<DynamicImage, String> {
+    let screenshot = driver
+        .screenshot_as_png()
+        .await
+        .map_err(|e| format!("Failed to take screenshot: {}", e))?;
+
+    let filename = format!("native_{}.png", index);
+    fs::write(SCREENSHOT_DIR.join(&filename), &screenshot)
+        .map_err(|e| format!("Failed to save {}: {}", filename, e))?;
+    info!("Saved {}", filename);
+
+    image::load_from_memory(&screenshot).map_err(|e| format!("Failed to load image: {}", e))
+}
+
+// 2枚のスクリーンショットの関係（px）
+#[derive(Debug)]
+struct ScrollLayout {
+    // 動かなかった下部の帯（タブバーなど）
+    fixed_bottom: u32,
+    // スクロールした量
+    offset: u32,
+    overlap_found: bool,
+}
+
+// 固定の帯を除いた領域で、前の画面の下側と今の画面の上側が一致するずれを探す
+// 画面が動いていなければ `None`
+fn find_scroll_layout(previous: &DynamicImage, current: &DynamicImage) -> Option<ScrollLayout> {
+    let a = previous.to_luma8();
+    let b = current.to_luma8();
+    if a.dimensions() != b.dimensions() {
+        return None;
+    }
+    let height = a.height();
+
+    // 動かなかった上下の帯（ステータスバー、ヘッダー、タブバーなど）
+    let fixed_top = (0..height)
+        .take_while(|&y| row_difference(&a, y, &b, y) < SAME_ROW_DIFFERENCE)
+        .count() as u32;
+    if fixed_top == height {
+        return None;
+    }
+    let fixed_bottom = (0..height - fixed_top)
+        .take_while(|&i| {
+            let y = height - 1 - i;
+            row_difference(&a, y, &b, y) < SAME_ROW_DIFFERENCE
+        })
+        .count() as u32;
+
+    let region_height = height - fixed_top - fixed_bottom;
+    let best = (0..region_height)
+        .map(|offset| {
+            let overlap = region_height - offset;
+            let step = (overlap / OVERLAP_SAMPLE_ROWS).max(1);
+            let rows = (0..overlap).step_by(step as usize);
+            let count = rows.clone().count().max(1) as f64;
+            let total: f64 = rows
+                .map(|y| row_difference(&a, fixed_top + offset + y, &b, fixed_top + y))
+                .sum();
+            (offset, total / count)
+        })
+        .min_by(|x, y| x.1.total_cmp(&y.1));
+
+    let (offset, overlap_found) = match best {
+        // 一部だけ変わった（アニメーションなど）が、スクロールはしていない
+        Some((0, _)) => return None,
+        Some((offset, difference)) if difference < OVERLAP_MAX_DIFFERENCE => (offset, true),
+        _ => (region_height, false),
+    };
+
+    Some(ScrollLayout {
+        fixed_bottom,
+        offset,
+        overlap_found,
+    })
+}
+
+// 1行の平均の差（4px ごとに間引く）
+fn row_difference(a: &GrayImage, ay: u32, b: &GrayImage, by: u32) -> f64 {
+    let width = a.width();
+    let columns = (0..width).step_by(4);
+    let count = columns.clone().count().max(1) as f64;
+    let total: u32 = columns
+        .map(|x| a.get_pixel(x, ay).0[0].abs_diff(b.get_pixel(x, by).0[0]) as u32)
+        .sum();
+    total as f64 / count
+}
+
+// 1枚目の上部＋スクロール領域、以降は新しく見えた部分、最後に下部の固定帯を並べる
+struct NativeStitcher {
+    strips: Vec<DynamicImage>,
+    fixed_bottom: u32,
+}
+
+impl NativeStitcher {
+    fn new(first: &DynamicImage) -> Self {
+        NativeStitcher {
+            strips: vec![first.clone()],
+            fixed_bottom: 0,
+        }
+    }
+
+    fn push(&mut self, current: &DynamicImage, layout: &ScrollLayout) {
+        let width = current.width();
+        let height = current.height();
+
+        // 1枚目は下部の固定帯を除く（最後に付け直す）
+        if self.strips.len() == 1 && layout.fixed_bottom > 0 {
+            self.strips[0] = self.strips[0].crop_imm(0, 0, width, height - layout.fixed_bottom);
+        }
+        self.fixed_bottom = layout.fixed_bottom;
+
+        let region_bottom = height - layout.fixed_bottom;
+        self.strips
+            .push(current.crop_imm(0, region_bottom - layout.offset, width, layout.offset));
+    }
+
+    fn finish(mut self, last: &DynamicImage) -> Result<Vec<Vec<u8>>, String> {
+        if self.fixed_bottom > 0 {
+            let height = last.height();
+            self.strips.push(last.crop_imm(
+                0,
+                height - self.fixed_bottom,
+                last.width(),
+                self.fixed_bottom,
+            ));
+        }
+
+        self.strips
+            .iter()
+            .map(|strip| {
+                let mut output = std::io::Cursor::new(Vec::new());
+                strip
+                    .write_to(&mut output, image::ImageFormat::Png)
+                    .map_err(|e| format!("Failed to encode image: {}", e))?;
+                Ok(output.into_inner())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 200;
+
+    // 行ごとに模様が異なる画像（`top` 行目から描く）
+    fn page(top: u32) -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(WIDTH, HEIGHT, |x, y| {
+            let hash = (y + top).wrapping_mul(2654435761) ^ x.wrapping_mul(40503);
+            Luma([(hash >> 11) as u8])
+        }))
+    }
+
+    #[test]
+    fn identical_screens_did_not_move() {
+        assert!(find_scroll_layout(&page(0), &page(0)).is_none());
+    }
+
+    #[test]
+    fn partly_changed_screen_did_not_move() {
+        let previous = page(0);
+        let mut current = previous.to_luma8();
+        for x in 0..WIDTH {
+            current.put_pixel(
+                x,
+                HEIGHT / 2,
+                Luma([255 - current.get_pixel(x, HEIGHT / 2).0[0]]),
+            );
+        }
+        assert!(find_scroll_layout(&previous, &DynamicImage::ImageLuma8(current)).is_none());
+    }
+
+    #[test]
+    fn shifted_screens_find_offset() {
+        let layout = find_scroll_layout(&page(0), &page(37)).unwrap();
+        assert_eq!(layout.offset, 37);
+        assert_eq!(layout.fixed_bottom, 0);
+        assert!(layout.overlap_found);
+    }
+}
@@ -0,0 +1,95 @@
+use log::info;
+use serde::Deserialize;
+use thirtyfour::common::print::{PrintMargins, PrintOrientation, PrintPage, PrintParameters};
+use thirtyfour::prelude::*;
+
+// 用紙サイズ（cm）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PageSize {
+    #[default]
+    A4,
+    A3,
+    Letter,
+    Legal,
+    Custom {
+        width: f64,
+        height: f64,
+    },
+}
+
+impl PageSize {
+    fn dimensions(self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (21.0, 29.7),
+            PageSize::A3 => (29.7, 42.0),
+            PageSize::Letter => (21.59, 27.94),
+            PageSize::Legal => (21.59, 35.56),
+            PageSize::Custom { width, height } => (width, height),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+// W3C の印刷エンドポイントで PDF も保存する（Chrome / Firefox）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PdfOptions {
+    pub enabled: bool,
+    pub page_size: PageSize,
+    // 上下左右の余白（cm）
+    pub margin: f64,
+    // 0.1〜2.0
+    pub scale: f64,
+    pub orientation: Orientation,
+    // 背景色・背景画像も印刷する
+    pub background: bool,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        PdfOptions {
+            enabled: false,
+            page_size: PageSize::default(),
+            margin: 1.0,
+            scale: 1.0,
+            orientation: Orientation::default(),
+            background: true,
+        }
+    }
+}
+
+// `POST /session/{id}/print` で現在のページを PDF にする
+pub async fn print_pdf(driver: &WebDriver, options: &PdfOptions) -> Result<Vec<u8>, String> {
+    info!("Printing page to PDF...");
+    let (width, height) = options.page_size.dimensions();
+
+    let parameters = PrintParameters {
+        orientation: match options.orientation {
+            Orientation::Portrait => PrintOrientation::Portrait,
+            Orientation::Landscape => PrintOrientation::Landscape,
+        },
+        scale: options.scale.clamp(0.1, 2.0),
+        background: options.background,
+        page: PrintPage { width, height },
+        margin: PrintMargins {
+            top: options.margin,
+            bottom: options.margin,
+            left: options.margin,
+            right: options.margin,
+        },
+        ..PrintParameters::default()
+    };
+
+    driver
+        .print_page(parameters)
+        .await
+        .map_err(|e| format!("Failed to print PDF: {}", e))
+}
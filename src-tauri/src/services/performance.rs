@@ -0,0 +1,92 @@
+use log::info;
+use serde_json::Value;
+use thirtyfour::prelude::*;
+
+// LCP と CLS を記録する（読み込み前の分も `buffered` で受け取る。何度実行しても1回だけ仕込む）
+pub const PERFORMANCE_OBSERVER_SCRIPT: &str = r#"
+    if (!window.__scshokiPerformance) {
+        const metrics = window.__scshokiPerformance = { lcp: null, cls: null };
+        const observe = (type, callback) => {
+            if (!window.PerformanceObserver || !PerformanceObserver.supportedEntryTypes.includes(type)) return false;
+            new PerformanceObserver((list) => list.getEntries().forEach(callback)).observe({ type, buffered: true });
+            return true;
+        };
+
+        observe("largest-contentful-paint", (entry) => {
+            metrics.lcp = entry.startTime;
+        });
+
+        // 1秒以内に続くずれを1つのまとまり（最大5秒）とし、最も大きいまとまりの合計
+        let session = 0, sessionStart = 0, last = 0;
+        const supported = observe("layout-shift", (entry) => {
+            if (entry.hadRecentInput) return;
+            if (session > 0 && entry.startTime - last < 1000 && entry.startTime - sessionStart < 5000) {
+                session += entry.value;
+            } else {
+                session = entry.value;
+                sessionStart = entry.startTime;
+            }
+            last = entry.startTime;
+            metrics.cls = Math.max(metrics.cls || 0, session);
+        });
+        if (supported && metrics.cls === null) metrics.cls = 0;
+    }
+"#;
+
+// Navigation Timing・ペイント・転送量などをまとめる（時間はナビゲーション開始からのミリ秒）
+const COLLECT_METRICS_SCRIPT: &str = r#"
+    const navigation = performance.getEntriesByType("navigation")[0];
+    const paint = (name) => {
+        const entry = performance.getEntriesByName(name, "paint")[0];
+        return entry ? entry.startTime : null;
+    };
+    const span = (start, end) => (start > 0 && end >= start ? end - start : null);
+    const resources = performance.getEntriesByType("resource");
+    const observed = window.__scshokiPerformance || { lcp: null, cls: null };
+    // 別オリジンで `Timing-Allow-Origin` がない通信は 0 になる
+    const transferSize = [navigation, ...resources]
+        .filter(Boolean)
+        .reduce((total, e) => total + (e.transferSize || 0), 0);
+
+    return {
+        url: location.href,
+        navigation: navigation ? {
+            type: navigation.type,
+            redirectCount: navigation.redirectCount,
+            protocol: navigation.nextHopProtocol,
+            dns: span(navigation.domainLookupStart, navigation.domainLookupEnd),
+            connect: span(navigation.connectStart, navigation.connectEnd),
+            tls: span(navigation.secureConnectionStart, navigation.connectEnd),
+            timeToFirstByte: navigation.responseStart,
+            response: span(navigation.responseStart, navigation.responseEnd),
+            domInteractive: navigation.domInteractive,
+            domContentLoaded: navigation.domContentLoadedEventEnd,
+            load: navigation.loadEventEnd,
+        } : null,
+        firstPaint: paint("first-paint"),
+        firstContentfulPaint: paint("first-contentful-paint"),
+        // 対応していないブラウザ（Safari など）は null
+        largestContentfulPaint: observed.lcp,
+        cumulativeLayoutShift: observed.cls,
+        transferSize,
+        resourceCount: resources.length,
+        domNodeCount: document.getElementsByTagName("*").length,
+    };
+"#;
+
+// 読み込み時に仕込んだ監視の結果と Performance API の値を集める
+pub async fn collect_performance_metrics(driver: &WebDriver) -> Result<Value, String> {
+    let metrics = driver
+        .execute(COLLECT_METRICS_SCRIPT, vec![])
+        .await
+        .map_err(|e| format!("Failed to collect performance metrics: {}", e))?
+        .json()
+        .clone();
+    info!(
+        "Performance metrics: FCP {} ms, LCP {} ms, CLS {}",
+        metrics["firstContentfulPaint"],
+        metrics["largestContentfulPaint"],
+        metrics["cumulativeLayoutShift"]
+    );
+    Ok(metrics)
+}
@@ -0,0 +1,64 @@
+use base64::prelude::*;
+use log::info;
+use serde::Deserialize;
+use serde_json::json;
+use thirtyfour::prelude::*;
+
+use crate::services::webrdiver::SessionCommand;
+
+// スクロールしながら撮影する様子を Appium で録画する（Android / iOS）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordingOptions {
+    pub enabled: bool,
+    // ビットレート（bps、Android のみ）
+    pub bit_rate: u32,
+    // 録画の上限（秒、Android は最大1800秒）
+    pub time_limit: u32,
+}
+
+impl Default for RecordingOptions {
+    fn default() -> Self {
+        RecordingOptions {
+            enabled: false,
+            bit_rate: 4_000_000,
+            time_limit: 180,
+        }
+    }
+}
+
+// `startRecordingScreen` で録画を始める
+pub async fn start_recording(driver: &WebDriver, options: &RecordingOptions) -> Result<(), String> {
+    info!("Starting screen recording...");
+    let body = json!({
+        "options": {
+            "bitRate": options.bit_rate,
+            "timeLimit": options.time_limit,
+            // 前回の録画が残っていても始め直す
+            "forceRestart": true,
+        }
+    });
+
+    SessionCommand::post("appium/start_recording_screen", body)
+        .send(driver)
+        .await
+        .map_err(|e| format!("Failed to start screen recording: {}", e))?;
+    Ok(())
+}
+
+// `stopRecordingScreen` で録画を止め、MP4 を返す
+pub async fn stop_recording(driver: &WebDriver) -> Result<Vec<u8>, String> {
+    let video = SessionCommand::post("appium/stop_recording_screen", json!({}))
+        .send(driver)
+        .await
+        .map_err(|e| format!("Failed to stop screen recording: {}", e))?;
+
+    let video = BASE64_STANDARD
+        .decode(video.as_str().unwrap_or_default())
+        .map_err(|e| format!("Failed to decode screen recording: {}", e))?;
+    if video.is_empty() {
+        return Err("Screen recording is empty".to_string());
+    }
+    info!("Stopped screen recording ({} bytes)", video.len());
+    Ok(video)
+}
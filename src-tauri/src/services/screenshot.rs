@@ -1,31 +1,303 @@
-use image::{DynamicImage, GenericImageView, ImageBuffer};
+use base64::prelude::*;
+use image::{DynamicImage, GenericImageView, ImageBuffer, RgbaImage};
 use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::error::Error;
 use std::fs;
+use thirtyfour::extensions::cdp::ChromeDevTools;
 use thirtyfour::prelude::*;
+use tokio::task::spawn_blocking;
+use tokio::time::{sleep, Duration, Instant};
 
-use crate::config::constants::SCREENSHOT_DIR;
+use crate::config::constants::{DEVICE_OS, SCREENSHOT_DIR};
+use crate::services::audit::{AccessibilityAudit, AuditOptions};
+use crate::services::blocking::RequestBlocking;
+use crate::services::cdp::CdpSession;
+use crate::services::consent::{ConsentOptions, ConsentResult};
+use crate::services::console::ConsoleEntry;
+use crate::services::device::adb::screencap;
 use crate::services::dom::{
-    get_page_metrics, get_scroll_position, hide_elements, scroll_by, show_elements,
+    find_text_matches, get_element_rects, get_page_metrics, get_scroll_position, hide_elements,
+    show_elements, unmatched_warning, Determinism, DocumentRect, DomSnapshot, ElementTarget,
+    HidePolicy, HideRule, HighlightRule, MaskRule, TextRedaction,
 };
-use crate::services::image::{cut_scroll_overlap, trim_extra_space};
-use crate::utils::wait::{wait_for_elements_hidden, wait_for_scroll_complete};
+use crate::services::gesture::{scroll_page, ScrollDriver};
+use crate::services::hooks::{run_hooks, HookPoint, InjectionHook};
+use crate::services::image::{
+    crop_to_rect, cut_scroll_overlap, is_uniform_color, mean_difference, tile_thumbnail,
+    trim_extra_space, HighlightRegion, MaskEffect, MaskRegion,
+};
+use crate::services::pdf::PdfOptions;
+use crate::services::recording::RecordingOptions;
+use crate::services::states::StateCapture;
+use crate::services::webrdiver::SessionCommand;
+use crate::services::webview::WebviewTarget;
+use crate::utils::wait::{wait_for_elements_hidden, wait_for_scroll_complete, WaitStrategy};
+
+// 前のタイルとの平均の差がこれ未満なら同じ画面とみなす
+const NEAR_IDENTICAL_DIFFERENCE: f64 = 1.0;
+// 前のタイルと比べるのは、表示領域の高さのこの割合以上スクロールした場合だけ
+const MIN_COMPARED_SCROLL_RATIO: f64 = 0.5;
+
+// フロントエンドから受け取る撮影オプション
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CaptureOptions {
+    pub hidden_elements: Vec<HideRule>,
+    pub mask_rules: Vec<MaskRule>,
+    pub text_redaction: TextRedaction,
+    pub highlight_rules: Vec<HighlightRule>,
+    pub hooks: Vec<InjectionHook>,
+    pub determinism: Determinism,
+    pub wait_strategies: Vec<WaitStrategy>,
+    pub scroll_driver: ScrollDriver,
+    pub browser: String,
+    pub backend: CaptureBackend,
+    // adb で操作する Android 端末のシリアル（未指定なら接続中の1台）
+    pub android_serial: Option<String>,
+    pub tile_source: TileSource,
+    pub tile_validation: TileValidation,
+    pub state_captures: Vec<StateCapture>,
+    pub webview: WebviewTarget,
+    pub pdf: PdfOptions,
+    // コンソール出力と JavaScript のエラーを JSON に保存
+    pub console_log: bool,
+    // 通信の一覧を HAR で保存
+    pub network_log: bool,
+    // DOM・タイトル・説明文・表示中のテキストを保存
+    pub dom_snapshot: bool,
+    pub audit: AuditOptions,
+    // 広告・トラッカーなどの通信をブロック
+    pub blocking: RequestBlocking,
+    // Cookie 同意バナーを閉じる
+    pub consent: ConsentOptions,
+    // 撮影中の画面を MP4 で録画
+    pub recording: RecordingOptions,
+    // 読み込みの速さ（Navigation Timing・FCP・LCP・CLS など）を JSON に保存
+    pub performance_metrics: bool,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        CaptureOptions {
+            hidden_elements: vec![],
+            mask_rules: vec![],
+            text_redaction: TextRedaction::default(),
+            highlight_rules: vec![],
+            hooks: vec![],
+            determinism: Determinism::default(),
+            wait_strategies: vec![],
+            scroll_driver: ScrollDriver::default(),
+            browser: "safari".to_string(),
+            backend: CaptureBackend::default(),
+            android_serial: None,
+            tile_source: TileSource::default(),
+            tile_validation: TileValidation::default(),
+            state_captures: vec![],
+            webview: WebviewTarget::default(),
+            pdf: PdfOptions::default(),
+            console_log: false,
+            network_log: false,
+            dom_snapshot: false,
+            audit: AuditOptions::default(),
+            blocking: RequestBlocking::default(),
+            consent: ConsentOptions::default(),
+            recording: RecordingOptions::default(),
+            performance_metrics: false,
+        }
+    }
+}
+
+// ブラウザの操作方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureBackend {
+    // Appium（WebDriver）経由
+    #[default]
+    Appium,
+    // adb で転送した Android Chrome の DevTools に直接接続
+    CdpAdb,
+    // アプリ内の WebView（アプリ内ブラウザ、ハイブリッドアプリ）に切り替えて Appium で撮影
+    Webview,
+}
+
+// スクロール中の各スクリーンショットの取得方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TileSource {
+    // WebDriver の `screenshot_as_png`
+    #[default]
+    Webdriver,
+    // `adb exec-out screencap -p` を WebView の矩形で切り出す（Android のみ）
+    AdbScreencap,
+}
+
+// 描画途中のスクリーンショットを撮り直すための検証
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TileValidation {
+    pub enabled: bool,
+    pub max_retries: u32,
+    // 撮り直す前に待つ時間
+    pub settle_ms: u64,
+    // 表示されている間は撮り直すローディング表示
+    pub spinner: Option<ElementTarget>,
+}
+
+impl Default for TileValidation {
+    fn default() -> Self {
+        TileValidation {
+            enabled: false,
+            max_retries: 2,
+            settle_ms: 500,
+            spinner: None,
+        }
+    }
+}
+
+// `adb screencap` で撮影したときの所要時間
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TileTiming {
+    pub tiles: u32,
+    pub screencap_ms: u64,
+    // 同じ枚数を WebDriver で撮った場合の見積もり（1枚目で計測）
+    pub estimated_webdriver_ms: u64,
+}
+
+// 撮影方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMethod {
+    // スクロールしながら撮影して結合
+    #[default]
+    Stitched,
+    // ブラウザのネイティブ API でページ全体を一度に撮影
+    Native,
+}
+
+// スクロールしながら撮影した結果
+#[derive(Debug, Default)]
+pub struct FullPageCapture {
+    pub screenshots: Vec<Vec<u8>>,
+    // 撮影は続行できたが確認が必要な内容（一致する要素がない等）
+    pub warnings: Vec<String>,
+    // 結合後の画像に適用するマスク（ドキュメント座標）
+    pub mask_regions: Vec<MaskRegion>,
+    // 正規表現に一致したテキストの数
+    pub redacted_matches: u64,
+    // 結合後の画像に描画する枠線（ドキュメント座標）
+    pub highlight_regions: Vec<HighlightRegion>,
+    pub method: CaptureMethod,
+    pub tile_timing: Option<TileTiming>,
+    // 検証に失敗して撮り直した回数
+    pub tile_retries: u32,
+    // 要素の状態ごとのスクリーンショットの一覧（JSON）のパス
+    pub state_sets: Vec<String>,
+    // スクリーンショットと同じ名前で保存する PDF
+    pub pdf: Option<Vec<u8>>,
+    pub console_entries: Option<Vec<ConsoleEntry>>,
+    pub har: Option<Value>,
+    pub dom_snapshot: Option<DomSnapshot>,
+    pub audit: Option<AccessibilityAudit>,
+    // ブロックした通信の数
+    pub blocked_requests: Option<u64>,
+    // Cookie 同意バナーに一致したルール
+    pub consent: Option<ConsentResult>,
+    // スクリーンショットと同じ名前で保存する録画
+    pub recording: Option<Vec<u8>>,
+    pub performance_metrics: Option<Value>,
+}
+
+impl FullPageCapture {
+    // 同じ警告は一度だけ記録する
+    pub fn warn(&mut self, message: String) {
+        if !self.warnings.contains(&message) {
+            self.warnings.push(message);
+        }
+    }
+
+    // 同じ位置のマスクは一度だけ記録する
+    fn add_mask(&mut self, rect: DocumentRect, effect: &MaskEffect) {
+        let exists = self
+            .mask_regions
+            .iter()
+            .any(|m| m.effect == *effect && same_rect(&m.rect, &rect));
+        if !exists {
+            self.mask_regions.push(MaskRegion {
+                rect,
+                effect: effect.clone(),
+            });
+        }
+    }
+
+    // 同じ位置の枠線は一度だけ記録する
+    fn add_highlight(&mut self, rect: DocumentRect, rule: &HighlightRule) {
+        let exists = self
+            .highlight_regions
+            .iter()
+            .any(|h| same_rect(&h.rect, &rect));
+        if !exists {
+            self.highlight_regions.push(HighlightRegion {
+                rect,
+                color: rule.color.clone(),
+                label: rule.label,
+            });
+        }
+    }
+}
+
+// 小数点以下の誤差を無視して同じ矩形か判定
+fn same_rect(a: &DocumentRect, b: &DocumentRect) -> bool {
+    (a.x - b.x).abs() < 1.0
+        && (a.y - b.y).abs() < 1.0
+        && (a.width - b.width).abs() < 1.0
+        && (a.height - b.height).abs() < 1.0
+}
+
+// ページの大きさ（CSS px）
+struct PageMetrics {
+    inner_width: f64,
+    inner_height: f64,
+    total_scroll_height: f64,
+    scroll_steps: u32,
+}
 
 pub async fn capture_full_page(
     driver: &WebDriver,
-    hidden_elements: &str,
-) -> Result<Vec<Vec<u8>>, String> {
+    options: &CaptureOptions,
+) -> Result<FullPageCapture, String> {
     info!("Capturing full page screenshot...");
+    let mut capture = FullPageCapture::default();
+
+    // `never_in_output` の要素はメトリクス取得前に非表示にする
+    let mut hidden_states = vec![false; options.hidden_elements.len()];
+    for (rule, hidden) in options.hidden_elements.iter().zip(hidden_states.iter_mut()) {
+        if rule.policy == HidePolicy::NeverInOutput {
+            set_rule_hidden(driver, rule, true, &mut capture).await?;
+            *hidden = true;
+        }
+    }
+
+    // 最初のスクリーンショットの前のフック（レイアウトが変わることがあるのでメトリクス取得前）
+    for warning in run_hooks(driver, &options.hooks, HookPoint::BeforeFirstTile).await {
+        capture.warn(warning);
+    }
 
     // ページの各種メトリクスを取得
     let metrics = get_page_metrics(driver)
         .await
         .map_err(|e| format!("Failed to get page metrics: {}", e))?;
 
-    let total_scroll_height = *metrics.get("totalScrollHeight").unwrap_or(&0.0);
-    let inner_height = *metrics.get("innerHeight").unwrap_or(&0.0);
-    let scroll_steps = *metrics.get("scrollSteps").unwrap_or(&0.0) as u32;
+    let metrics = PageMetrics {
+        inner_width: *metrics.get("innerWidth").unwrap_or(&0.0),
+        inner_height: *metrics.get("innerHeight").unwrap_or(&0.0),
+        total_scroll_height: *metrics.get("totalScrollHeight").unwrap_or(&0.0),
+        scroll_steps: *metrics.get("scrollSteps").unwrap_or(&0.0) as u32,
+    };
 
-    if total_scroll_height <= 0.0 {
+    if metrics.total_scroll_height <= 0.0 {
         return Err("Failed to retrieve page height.".to_string());
     }
 
@@ -36,65 +308,583 @@ pub async fn capture_full_page(
             .map_err(|e| format!("Failed to create screenshots directory: {}", e))?;
     }
 
+    let mut matched = RegionMatches::new(options);
+
+    // ページ全体を一度に撮れるブラウザではまずそちらを試し、失敗したらスクロールして結合する
+    let native = if supports_native_full_page(&options.browser) {
+        capture_native(
+            driver,
+            options,
+            &metrics,
+            &mut hidden_states,
+            &mut matched,
+            &mut capture,
+        )
+        .await
+        .inspect_err(|e| {
+            capture.warn(format!(
+                "Native full-page screenshot failed, falling back to stitching: {}",
+                e
+            ))
+        })
+        .is_ok()
+    } else {
+        false
+    };
+
+    if !native {
+        // 一括撮影で記録した位置は使わない
+        capture.mask_regions.clear();
+        capture.highlight_regions.clear();
+        matched = RegionMatches::new(options);
+        capture_tiles(
+            driver,
+            options,
+            &metrics,
+            &mut hidden_states,
+            &mut matched,
+            &mut capture,
+        )
+        .await?;
+    }
+
+    matched.warn_unmatched(driver, options, &mut capture).await;
+
+    // 非表示にした要素を元に戻す
+    for (rule, hidden) in options.hidden_elements.iter().zip(hidden_states) {
+        if hidden {
+            set_rule_hidden(driver, rule, false, &mut capture).await?;
+        }
+    }
+
+    Ok(capture)
+}
+
+// CDP 経由でページ全体を一度に撮影（要素の操作は WebDriver が必要なので未対応）
+pub async fn capture_full_page_cdp(
+    session: &mut CdpSession,
+    options: &CaptureOptions,
+) -> Result<FullPageCapture, String> {
+    info!("Capturing full page screenshot over CDP...");
+    let mut capture = FullPageCapture::default();
+
+    let unsupported = [
+        ("hidden elements", !options.hidden_elements.is_empty()),
+        ("state captures", !options.state_captures.is_empty()),
+        ("console log", options.console_log),
+        ("network log", options.network_log),
+        ("DOM snapshot", options.dom_snapshot),
+        ("accessibility audit", options.audit.enabled),
+        ("request blocking", options.blocking.enabled),
+        ("consent banner dismissal", options.consent.enabled),
+        ("screen recording", options.recording.enabled),
+        ("performance metrics", options.performance_metrics),
+        ("PDF output", options.pdf.enabled),
+        ("mask rules", !options.mask_rules.is_empty()),
+        (
+            "text redaction",
+            !options.text_redaction.patterns().is_empty(),
+        ),
+        ("highlight rules", !options.highlight_rules.is_empty()),
+        ("injection hooks", !options.hooks.is_empty()),
+        ("deterministic mode", options.determinism.enabled),
+        ("wait strategies", !options.wait_strategies.is_empty()),
+        (
+            "scroll driver",
+            options.scroll_driver != ScrollDriver::Script,
+        ),
+    ];
+    for (name, _) in unsupported.iter().filter(|(_, used)| *used) {
+        capture.warn(format!("The CDP over ADB backend ignores {}", name));
+    }
+
+    if !SCREENSHOT_DIR.exists() {
+        info!("Creating screenshots directory...");
+        fs::create_dir(&*SCREENSHOT_DIR)
+            .map_err(|e| format!("Failed to create screenshots directory: {}", e))?;
+    }
+
+    capture.screenshots.push(session.capture_full_page().await?);
+    capture.method = CaptureMethod::Native;
+    Ok(capture)
+}
+
+// ルールごとにこのスクリーンショットでの表示・非表示を切り替える
+async fn apply_hide_policies(
+    driver: &WebDriver,
+    options: &CaptureOptions,
+    hidden_states: &mut [bool],
+    index: u32,
+    is_last: bool,
+    capture: &mut FullPageCapture,
+) -> Result<(), String> {
+    for (rule, hidden) in options.hidden_elements.iter().zip(hidden_states.iter_mut()) {
+        let should_hide = rule.policy.hides_tile(index, is_last);
+        if should_hide != *hidden {
+            set_rule_hidden(driver, rule, should_hide, capture).await?;
+            *hidden = should_hide;
+        }
+    }
+    Ok(())
+}
+
+// ブラウザのネイティブ API でページ全体を一度に撮影（1枚目かつ最後のスクリーンショットとして扱う）
+async fn capture_native(
+    driver: &WebDriver,
+    options: &CaptureOptions,
+    metrics: &PageMetrics,
+    hidden_states: &mut [bool],
+    matched: &mut RegionMatches,
+    capture: &mut FullPageCapture,
+) -> Result<(), String> {
+    info!(
+        "Taking native full-page screenshot ({})...",
+        options.browser
+    );
+
+    for warning in run_hooks(driver, &options.hooks, HookPoint::BeforeEachTile).await {
+        capture.warn(warning);
+    }
+    apply_hide_policies(driver, options, hidden_states, 1, true, capture).await?;
+    record_regions(driver, options, false, capture, matched).await?;
+
+    let screenshot = native_full_page_screenshot(driver, &options.browser, metrics)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    capture.screenshots.push(screenshot);
+    capture.method = CaptureMethod::Native;
+    info!("Native full-page screenshot captured.");
+    Ok(())
+}
+
+fn supports_native_full_page(browser: &str) -> bool {
+    matches!(browser, "chrome" | "firefox")
+}
+
+// Firefox は `moz/screenshot/full`、Chrome は CDP の `captureBeyondViewport` で撮影
+async fn native_full_page_screenshot(
+    driver: &WebDriver,
+    browser: &str,
+    metrics: &PageMetrics,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let data = match browser {
+        "firefox" => SessionCommand::get("moz/screenshot/full")
+            .send(driver)
+            .await?
+            .as_str()
+            .ok_or("Unexpected response from moz/screenshot/full")?
+            .to_string(),
+        "chrome" => {
+            let devtools = ChromeDevTools::new(driver.handle.clone());
+            let result = devtools
+                .execute_cdp_with_params(
+                    "Page.captureScreenshot",
+                    json!({
+                        "format": "png",
+                        "captureBeyondViewport": true,
+                        "clip": {
+                            "x": 0,
+                            "y": 0,
+                            "width": metrics.inner_width,
+                            "height": metrics.total_scroll_height,
+                            "scale": 1,
+                        },
+                    }),
+                )
+                .await?;
+            result["data"]
+                .as_str()
+                .ok_or("Unexpected response from Page.captureScreenshot")?
+                .to_string()
+        }
+        _ => return Err(format!("Unsupported browser: {}", browser).into()),
+    };
+
+    Ok(BASE64_STANDARD.decode(data)?)
+}
+
+// スクロール中のスクリーンショットを撮る（`adb screencap` が失敗したら WebDriver に切り替える）
+struct TileCamera {
+    source: TileSource,
+    // WebView の矩形（デバイスの px: left, top, width, height）
+    content_rect: Option<(u32, u32, u32, u32)>,
+    webdriver_sample_ms: u64,
+    timing: TileTiming,
+}
+
+impl TileCamera {
+    fn new(source: TileSource) -> Self {
+        TileCamera {
+            source,
+            content_rect: None,
+            webdriver_sample_ms: 0,
+            timing: TileTiming::default(),
+        }
+    }
+
+    async fn take(
+        &mut self,
+        driver: &WebDriver,
+        options: &CaptureOptions,
+        capture: &mut FullPageCapture,
+    ) -> Result<Vec<u8>, String> {
+        if self.source == TileSource::AdbScreencap {
+            match self.screencap(driver, options).await {
+                Ok(screenshot) => return Ok(screenshot),
+                Err(e) => {
+                    capture.warn(format!(
+                        "adb screencap failed, using WebDriver screenshots instead: {}",
+                        e
+                    ));
+                    self.source = TileSource::Webdriver;
+                }
+            }
+        }
+
+        driver
+            .screenshot_as_png()
+            .await
+            .map_err(|e| format!("Failed to take screenshot: {}", e))
+    }
+
+    async fn screencap(
+        &mut self,
+        driver: &WebDriver,
+        options: &CaptureOptions,
+    ) -> Result<Vec<u8>, String> {
+        if self.content_rect.is_none() {
+            self.content_rect = Some(get_content_rect(driver).await?);
+
+            // 比較用に WebDriver でも1枚撮って時間を計る
+            let start_time = Instant::now();
+            driver
+                .screenshot_as_png()
+                .await
+                .map_err(|e| format!("Failed to take screenshot: {}", e))?;
+            self.webdriver_sample_ms = start_time.elapsed().as_millis() as u64;
+        }
+        let (left, top, width, height) = self.content_rect.unwrap_or_default();
+
+        let start_time = Instant::now();
+        // adb の完了を待つ間 tokio のワーカーを止めないよう、別スレッドで実行
+        let serial = options.android_serial.clone();
+        let screenshot = spawn_blocking(move || screencap(serial.as_deref()))
+            .await
+            .map_err(|e| format!("Failed to run adb screencap: {}", e))??;
+        let cropped = crop_to_rect(&screenshot, left, top, width, height)?;
+
+        self.timing.tiles += 1;
+        self.timing.screencap_ms += start_time.elapsed().as_millis() as u64;
+        self.timing.estimated_webdriver_ms += self.webdriver_sample_ms;
+        Ok(cropped)
+    }
+}
+
+// Android の WebView の矩形（デバイスの px）
+async fn get_content_rect(driver: &WebDriver) -> Result<(u32, u32, u32, u32), String> {
+    if DEVICE_OS.lock().unwrap().as_deref() != Some("Android") {
+        return Err("adb screencap is only available on Android".to_string());
+    }
+
+    let rect = driver
+        .execute("mobile: viewportRect", vec![])
+        .await
+        .map_err(|e| format!("Failed to get viewport rect: {}", e))?
+        .json()
+        .clone();
+
+    match (
+        rect["left"].as_u64(),
+        rect["top"].as_u64(),
+        rect["width"].as_u64(),
+        rect["height"].as_u64(),
+    ) {
+        (Some(left), Some(top), Some(width), Some(height)) => {
+            Ok((left as u32, top as u32, width as u32, height as u32))
+        }
+        _ => Err(format!("Unexpected viewport rect: {}", rect)),
+    }
+}
+
+// 撮り直しが必要なら理由を返す（`previous_thumbnail` は十分スクロールした場合だけ渡すので差があるはず）
+async fn validate_tile(
+    driver: &WebDriver,
+    validation: &TileValidation,
+    thumbnail: &RgbaImage,
+    previous_thumbnail: Option<&RgbaImage>,
+) -> Option<String> {
+    if is_uniform_color(thumbnail) {
+        return Some("the screenshot is a single color".to_string());
+    }
+
+    if let Some(previous) = previous_thumbnail {
+        if mean_difference(thumbnail, previous) < NEAR_IDENTICAL_DIFFERENCE {
+            return Some("the screenshot is nearly identical to the previous one".to_string());
+        }
+    }
+
+    if let Some(spinner) = &validation.spinner {
+        match get_element_rects(driver, spinner, true).await {
+            Ok(rects) if !rects.is_empty() => {
+                return Some(format!(
+                    "loading indicator is visible: {}",
+                    spinner.selector
+                ));
+            }
+            Ok(_) => {}
+            Err(e) => debug!("Failed to check loading indicator: {}", e),
+        }
+    }
+
+    None
+}
+
+// スクロールしながらスクリーンショットを撮影
+async fn capture_tiles(
+    driver: &WebDriver,
+    options: &CaptureOptions,
+    metrics: &PageMetrics,
+    hidden_states: &mut [bool],
+    matched: &mut RegionMatches,
+    capture: &mut FullPageCapture,
+) -> Result<(), String> {
+    let PageMetrics {
+        inner_width,
+        inner_height,
+        total_scroll_height,
+        scroll_steps,
+    } = *metrics;
+
     // 最初のスクリーンショット（ヘッダーあり）を撮影
     info!("Taking first screenshot...");
-    let mut screenshots = vec![];
+
+    // タッチ操作では1回のスクロール量が1画面より小さいので上限に余裕を持たせる
+    let max_tiles = scroll_steps.max(1) * 4;
+    // ここまでのスクリーンショットで撮影済みのドキュメントの下端
+    let mut captured_bottom = 0.0;
+    let mut y_offset = get_scroll_position(driver)
+        .await
+        .map_err(|e| format!("Failed to get scroll position: {}", e))?;
+    let mut camera = TileCamera::new(options.tile_source);
+    let validation = &options.tile_validation;
+    let mut previous_thumbnail = None;
+    // 直前のスクロールで実際に動いた量
+    let mut scrolled = 0.0;
 
     // スクロールしながらスクリーンショット
-    for index in 1..=scroll_steps {
+    for index in 1..=max_tiles {
         debug!("Starting scroll and caputure.");
 
-        // スクリーンショットを撮る
-        let screenshot: Vec<u8> = driver
-            .screenshot_as_png()
-            .await
-            .map_err(|e| format!("Failed to take screenshot: {}", e))?;
-
-        // 最後のスクロール時は、被った部分をカット
-        let cropped_screenshot = if index == scroll_steps {
-            // 被った部分を計算
-            let scroll_overlap_height = (inner_height * index as f64) - total_scroll_height;
-            // 余白をカットしてから被った部分をカット
-            let tmp = trim_extra_space(&screenshot, inner_height)?;
-            cut_scroll_overlap(&tmp, scroll_overlap_height)?
+        for warning in run_hooks(driver, &options.hooks, HookPoint::BeforeEachTile).await {
+            capture.warn(warning);
+        }
+
+        // ページ下端まで表示されていれば最後のスクリーンショット
+        let is_last = y_offset + inner_height >= total_scroll_height - 1.0 || index == max_tiles;
+        apply_hide_policies(driver, options, hidden_states, index, is_last, capture).await?;
+
+        // マスク・枠線の位置を記録（固定表示の要素もあるので毎回取得する）
+        record_regions(driver, options, true, capture, matched).await?;
+
+        // スクリーンショットを撮る（描画途中なら少し待って撮り直す）
+        let mut retries = 0;
+        let screenshot = loop {
+            let screenshot = camera.take(driver, options, capture).await?;
+            if !validation.enabled {
+                break screenshot;
+            }
+
+            let thumbnail = tile_thumbnail(&screenshot)?;
+            // 最後の短いスクロールなどでほとんど動いていなければ、同じ画面に見えて当然なので比べない
+            let compared = previous_thumbnail
+                .as_ref()
+                .filter(|_| scrolled >= inner_height * MIN_COMPARED_SCROLL_RATIO);
+            let problem = validate_tile(driver, validation, &thumbnail, compared).await;
+            match problem {
+                Some(problem) if retries < validation.max_retries => {
+                    retries += 1;
+                    capture.tile_retries += 1;
+                    capture.warn(format!(
+                        "Retrying screenshot_{} ({}/{}): {}",
+                        index, retries, validation.max_retries, problem
+                    ));
+                    sleep(Duration::from_millis(validation.settle_ms)).await;
+                }
+                problem => {
+                    if let Some(problem) = problem {
+                        capture.warn(format!(
+                            "Kept screenshot_{} after {} retries: {}",
+                            index, retries, problem
+                        ));
+                    }
+                    previous_thumbnail = Some(thumbnail);
+                    break screenshot;
+                }
+            }
+        };
+
+        // 前のスクリーンショットと被った部分をカット
+        let scroll_overlap_height = captured_bottom - y_offset;
+        // 余白をカットしてから被った部分をカット
+        let trimmed = trim_extra_space(&screenshot, inner_height)?;
+        let cropped_screenshot = if scroll_overlap_height > 0.0 {
+            cut_scroll_overlap(&trimmed, scroll_overlap_height)?
         } else {
-            trim_extra_space(&screenshot, inner_height)?
+            trimmed
         };
+        captured_bottom = y_offset + inner_height;
 
-        screenshots.push(cropped_screenshot.clone());
+        capture.screenshots.push(cropped_screenshot.clone());
 
         let filename = format!("screenshot_{}.png", index);
         fs::write(SCREENSHOT_DIR.join(filename), &cropped_screenshot)
             .map_err(|e| format!("Failed to save screenshot_{}: {}", index, e))?;
         info!("Saved screenshot_{}.png", index);
 
+        if is_last {
+            break;
+        }
+
         // スクロール実行
-        scroll_by(driver, inner_height)
+        scroll_page(driver, options.scroll_driver, inner_width, inner_height)
             .await
             .map_err(|e| format!("Failed to scroll: {}", e))?;
         wait_for_scroll_complete(driver).await?; // スクロール完了を待つ
 
-        // 新しいスクロール位置を取得
-        let y_offset = get_scroll_position(driver)
+        // 新しいスクロール位置を取得（スワイプでは移動量が一定でないので実測値で結合する）
+        let new_y_offset = get_scroll_position(driver)
             .await
             .map_err(|e| format!("Failed to get scroll position: {}", e))?;
-        info!("Scrolled to: {} px", y_offset);
+        info!("Scrolled to: {} px", new_y_offset);
 
-        // 最初のスクロール直後に指定した要素を非表示にする
-        if index == 1 {
-            hide_elements(driver, hidden_elements)
-                .await
-                .map_err(|e| format!("Failed to hide elements: {}", e))?;
-            wait_for_elements_hidden(driver, hidden_elements).await?; // 非表示完了を待つ
+        if new_y_offset <= y_offset {
+            capture.warn(format!(
+                "Page did not scroll past {} px of {} px.",
+                y_offset + inner_height,
+                total_scroll_height
+            ));
+            break;
         }
+        scrolled = new_y_offset - y_offset;
+        y_offset = new_y_offset;
     }
 
-    // 非表示にした要素を元に戻す
-    show_elements(driver, hidden_elements)
-        .await
-        .map_err(|e| format!("Failed to restore elements: {}", e))?;
+    capture.method = CaptureMethod::Stitched;
+    if camera.timing.tiles > 0 {
+        info!(
+            "adb screencap: {} tiles in {} ms (WebDriver estimate: {} ms)",
+            camera.timing.tiles, camera.timing.screencap_ms, camera.timing.estimated_webdriver_ms
+        );
+        capture.tile_timing = Some(camera.timing);
+    }
+    Ok(())
+}
+
+// ルールごとに一致する要素があったか（一致なしの警告用）
+struct RegionMatches {
+    mask: Vec<bool>,
+    highlight: Vec<bool>,
+}
+
+impl RegionMatches {
+    fn new(options: &CaptureOptions) -> Self {
+        RegionMatches {
+            mask: vec![false; options.mask_rules.len()],
+            highlight: vec![false; options.highlight_rules.len()],
+        }
+    }
+
+    async fn warn_unmatched(
+        &self,
+        driver: &WebDriver,
+        options: &CaptureOptions,
+        capture: &mut FullPageCapture,
+    ) {
+        for (rule, matched) in options.mask_rules.iter().zip(&self.mask) {
+            if !matched && !rule.target.is_empty() {
+                capture.warn(unmatched_warning(driver, "mask", &rule.target).await);
+            }
+        }
+        for (rule, matched) in options.highlight_rules.iter().zip(&self.highlight) {
+            if !matched && !rule.target.is_empty() {
+                capture.warn(unmatched_warning(driver, "highlight", &rule.target).await);
+            }
+        }
+    }
+}
+
+// マスク・枠線・塗りつぶすテキストの位置を記録
+async fn record_regions(
+    driver: &WebDriver,
+    options: &CaptureOptions,
+    clip_to_viewport: bool,
+    capture: &mut FullPageCapture,
+    matched: &mut RegionMatches,
+) -> Result<(), String> {
+    for (rule, matched) in options.mask_rules.iter().zip(matched.mask.iter_mut()) {
+        let rects = get_element_rects(driver, &rule.target, clip_to_viewport)
+            .await
+            .map_err(|e| format!("Failed to get mask rects: {}", e))?;
+        *matched |= !rects.is_empty();
+        for rect in rects {
+            capture.add_mask(rect, &rule.effect);
+        }
+    }
+
+    for (rule, matched) in options
+        .highlight_rules
+        .iter()
+        .zip(matched.highlight.iter_mut())
+    {
+        let rects = get_element_rects(driver, &rule.target, clip_to_viewport)
+            .await
+            .map_err(|e| format!("Failed to get highlight rects: {}", e))?;
+        *matched |= !rects.is_empty();
+        for rect in rects {
+            capture.add_highlight(rect, rule);
+        }
+    }
+
+    let redaction_patterns = options.text_redaction.patterns();
+    if !redaction_patterns.is_empty() {
+        let matches = find_text_matches(driver, &redaction_patterns, clip_to_viewport)
+            .await
+            .map_err(|e| format!("Failed to find text matches: {}", e))?;
+        for pattern in matches.invalid_patterns {
+            capture.warn(format!("Invalid redaction pattern: {}", pattern));
+        }
+        // 遅延読み込みでテキストが増えることがあるので最大値を採用
+        capture.redacted_matches = capture.redacted_matches.max(matches.count);
+        for rect in matches.rects {
+            capture.add_mask(rect, &options.text_redaction.effect);
+        }
+    }
+
+    Ok(())
+}
 
-    Ok(screenshots)
+// ルールの要素を非表示、または元に戻す
+async fn set_rule_hidden(
+    driver: &WebDriver,
+    rule: &HideRule,
+    hidden: bool,
+    capture: &mut FullPageCapture,
+) -> Result<(), String> {
+    if hidden {
+        let count = hide_elements(driver, rule)
+            .await
+            .map_err(|e| format!("Failed to hide elements: {}", e))?;
+        if count == 0 && !rule.target.is_empty() {
+            capture.warn(unmatched_warning(driver, "hide", &rule.target).await);
+            return Ok(());
+        }
+        wait_for_elements_hidden(driver, rule).await // 非表示完了を待つ
+    } else {
+        show_elements(driver, rule)
+            .await
+            .map_err(|e| format!("Failed to restore elements: {}", e))
+    }
 }
 
 // スクリーンショットを結合する関数
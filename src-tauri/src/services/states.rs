@@ -0,0 +1,341 @@
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fs;
+use thirtyfour::extensions::cdp::ChromeDevTools;
+use thirtyfour::prelude::*;
+use tokio::time::{sleep, Duration};
+
+use crate::config::constants::{DEVICE_DENSITY, SCREENSHOT_DIR};
+use crate::services::dom::{unmatched_warning, ElementTarget, FIND_ELEMENTS_SCRIPT};
+use crate::services::image::crop_to_rect;
+
+// 状態を切り替えてから描画が落ち着くまで待つ時間
+const STATE_SETTLE: Duration = Duration::from_millis(300);
+// CDP で要素を特定するための属性
+const STATE_MARKER: &str = "data-scshoki-state";
+
+// 撮影する要素の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractiveState {
+    // 何もしていない状態（比較用）
+    Default,
+    Hover,
+    Focus,
+    Active,
+    // クリックして開いた状態（メニューなど）
+    Expanded,
+}
+
+impl InteractiveState {
+    fn name(self) -> &'static str {
+        match self {
+            InteractiveState::Default => "default",
+            InteractiveState::Hover => "hover",
+            InteractiveState::Focus => "focus",
+            InteractiveState::Active => "active",
+            InteractiveState::Expanded => "expanded",
+        }
+    }
+
+    // CDP の `CSS.forcePseudoState` で再現できる擬似クラス
+    fn pseudo_class(self) -> Option<&'static str> {
+        match self {
+            InteractiveState::Hover => Some("hover"),
+            InteractiveState::Focus => Some("focus"),
+            InteractiveState::Active => Some("active"),
+            InteractiveState::Default | InteractiveState::Expanded => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateCapture {
+    #[serde(flatten)]
+    pub target: ElementTarget,
+    pub states: Vec<InteractiveState>,
+    // 要素の周囲に含める余白（CSS px、開いたメニューを含めたいときなど）
+    #[serde(default)]
+    pub padding: f64,
+}
+
+// 状態ごとのスクリーンショットの一覧（`state_<n>.json`）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StateSet {
+    selector: String,
+    shots: Vec<StateShot>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StateShot {
+    state: InteractiveState,
+    file: String,
+    // CDP で擬似クラスを強制したか（false なら W3C のポインタ操作などで再現）
+    forced: bool,
+}
+
+// 要素ごとに各状態のスクリーンショットを撮り、一覧を保存してそのパスを返す
+pub async fn capture_states(
+    driver: &WebDriver,
+    browser: &str,
+    captures: &[StateCapture],
+) -> (Vec<String>, Vec<String>) {
+    let mut manifests = vec![];
+    let mut warnings = vec![];
+
+    for (index, state_capture) in captures.iter().enumerate() {
+        let set_index = index + 1;
+        match capture_state_set(driver, browser, state_capture, set_index, &mut warnings).await {
+            Ok(path) => manifests.push(path),
+            Err(e) => warnings.push(format!(
+                "Failed to capture states of {}: {}",
+                state_capture.target.selector, e
+            )),
+        }
+    }
+
+    (manifests, warnings)
+}
+
+async fn capture_state_set(
+    driver: &WebDriver,
+    browser: &str,
+    state_capture: &StateCapture,
+    set_index: usize,
+    warnings: &mut Vec<String>,
+) -> Result<String, Box<dyn Error>> {
+    info!("Capturing states of {}", state_capture.target.selector);
+    let element = find_element(driver, &state_capture.target).await?;
+    element.scroll_into_view().await?;
+
+    let devtools = (browser == "chrome").then(|| ChromeDevTools::new(driver.handle.clone()));
+    let mut shots = vec![];
+
+    for &state in &state_capture.states {
+        let forced = match (&devtools, state.pseudo_class()) {
+            (Some(devtools), Some(pseudo_class)) => {
+                force_pseudo_state(devtools, driver, &element, &[pseudo_class])
+                    .await
+                    .inspect_err(|e| debug!("forcePseudoState failed: {}", e))
+                    .is_ok()
+            }
+            _ => false,
+        };
+        // 合成イベントでは `:hover` や `:active` にならないため、再現できなければ撮らない
+        if !forced && !enter_state(driver, &element, state).await? {
+            warnings.push(format!(
+                "Could not reproduce the {} state of {}",
+                state.name(),
+                state_capture.target.selector
+            ));
+            continue;
+        }
+        sleep(STATE_SETTLE).await;
+
+        let file = format!("state_{}_{}.png", set_index, state.name());
+        let screenshot = capture_element_region(driver, &element, state_capture.padding).await;
+
+        // 撮影に失敗しても次の状態に影響しないよう先に元に戻す
+        if let Some(devtools) = devtools.as_ref().filter(|_| forced) {
+            force_pseudo_state(devtools, driver, &element, &[]).await?;
+        } else {
+            leave_state(driver, &element, state).await?;
+        }
+
+        fs::write(SCREENSHOT_DIR.join(&file), screenshot?)?;
+        info!("Saved {}", file);
+        shots.push(StateShot {
+            state,
+            file,
+            forced,
+        });
+    }
+
+    let manifest_path = SCREENSHOT_DIR.join(format!("state_{}.json", set_index));
+    let set = StateSet {
+        selector: state_capture.target.selector.clone(),
+        shots,
+    };
+    fs::write(&manifest_path, serde_json::to_string_pretty(&set)?)?;
+
+    Ok(manifest_path.display().to_string())
+}
+
+async fn find_element(
+    driver: &WebDriver,
+    target: &ElementTarget,
+) -> Result<WebElement, Box<dyn Error>> {
+    let script = format!(
+        r#"
+        {}
+        return findElements(arguments[0])[0] || null;
+        "#,
+        FIND_ELEMENTS_SCRIPT
+    );
+    let ret = driver.execute(&script, vec![target.to_arg()]).await?;
+    if ret.json().is_null() {
+        return Err(unmatched_warning(driver, "state", target).await.into());
+    }
+    Ok(ret.element()?)
+}
+
+// 要素に印を付けて CDP のノードを探し、擬似クラスを強制する（空なら解除）
+async fn force_pseudo_state(
+    devtools: &ChromeDevTools,
+    driver: &WebDriver,
+    element: &WebElement,
+    pseudo_classes: &[&str],
+) -> Result<(), Box<dyn Error>> {
+    driver
+        .execute(
+            "arguments[0].setAttribute(arguments[1], '');",
+            vec![element.to_json()?, json!(STATE_MARKER)],
+        )
+        .await?;
+
+    devtools.execute_cdp("DOM.enable").await?;
+    devtools.execute_cdp("CSS.enable").await?;
+    let document = devtools
+        .execute_cdp_with_params("DOM.getDocument", json!({ "depth": 0 }))
+        .await?;
+    let node = devtools
+        .execute_cdp_with_params(
+            "DOM.querySelector",
+            json!({
+                "nodeId": document["root"]["nodeId"],
+                "selector": format!("[{}]", STATE_MARKER),
+            }),
+        )
+        .await?;
+    let node_id = node["nodeId"].as_u64().filter(|id| *id > 0);
+
+    driver
+        .execute(
+            "arguments[0].removeAttribute(arguments[1]);",
+            vec![element.to_json()?, json!(STATE_MARKER)],
+        )
+        .await?;
+
+    let node_id = node_id.ok_or("Element is not reachable from the top document")?;
+    devtools
+        .execute_cdp_with_params(
+            "CSS.forcePseudoState",
+            json!({ "nodeId": node_id, "forcedPseudoClasses": pseudo_classes }),
+        )
+        .await?;
+    Ok(())
+}
+
+// W3C のポインタ操作やフォーカスで状態を再現（できなければ false）
+async fn enter_state(
+    driver: &WebDriver,
+    element: &WebElement,
+    state: InteractiveState,
+) -> Result<bool, Box<dyn Error>> {
+    match state {
+        InteractiveState::Default => {}
+        // タッチ端末ではマウスのポインタ操作ができないことがある
+        InteractiveState::Hover => {
+            if let Err(e) = driver
+                .action_chain()
+                .move_to_element_center(element)
+                .perform()
+                .await
+            {
+                debug!("Pointer move failed: {}", e);
+                return Ok(false);
+            }
+        }
+        InteractiveState::Focus => element.focus().await?,
+        // ボタンを押したままにして、`leave_state` で離す
+        InteractiveState::Active => {
+            if let Err(e) = driver
+                .action_chain()
+                .click_and_hold_element(element)
+                .perform()
+                .await
+            {
+                debug!("Pointer down failed: {}", e);
+                return Ok(false);
+            }
+        }
+        InteractiveState::Expanded => element.click().await?,
+    }
+    Ok(true)
+}
+
+async fn leave_state(
+    driver: &WebDriver,
+    element: &WebElement,
+    state: InteractiveState,
+) -> Result<(), Box<dyn Error>> {
+    match state {
+        InteractiveState::Default => {}
+        // 要素の外にポインタを移す
+        InteractiveState::Hover => {
+            driver.action_chain().move_to(0, 0).perform().await?;
+        }
+        InteractiveState::Focus => {
+            driver
+                .execute("arguments[0].blur();", vec![element.to_json()?])
+                .await?;
+        }
+        // クリックにならないよう、要素の外に移してから離す
+        InteractiveState::Active => {
+            driver
+                .action_chain()
+                .move_to(0, 0)
+                .release()
+                .perform()
+                .await?;
+            driver.action_chain().reset_actions().await?;
+        }
+        // もう一度クリックして閉じる
+        InteractiveState::Expanded => element.click().await?,
+    }
+    Ok(())
+}
+
+// 表示領域のスクリーンショットから要素の部分（余白込み）を切り出す
+async fn capture_element_region(
+    driver: &WebDriver,
+    element: &WebElement,
+    padding: f64,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let rect: Value = driver
+        .execute(
+            r#"
+            const rect = arguments[0].getBoundingClientRect();
+            const padding = arguments[1];
+            const left = Math.max(rect.left - padding, 0);
+            const top = Math.max(rect.top - padding, 0);
+            const right = Math.min(rect.right + padding, window.innerWidth);
+            const bottom = Math.min(rect.bottom + padding, window.innerHeight);
+            return { x: left, y: top, width: right - left, height: bottom - top };
+            "#,
+            vec![element.to_json()?, json!(padding)],
+        )
+        .await?
+        .json()
+        .clone();
+
+    let screenshot = driver.screenshot_as_png().await?;
+    let image = image::load_from_memory(&screenshot)?;
+    let density = DEVICE_DENSITY.lock().unwrap().unwrap_or(1.0);
+
+    let to_px = |key: &str| (rect[key].as_f64().unwrap_or(0.0) * density).round() as u32;
+    let left = to_px("x").min(image.width());
+    let top = to_px("y").min(image.height());
+    let width = to_px("width").min(image.width() - left);
+    let height = to_px("height").min(image.height() - top);
+    if width == 0 || height == 0 {
+        return Err("Element is not visible".into());
+    }
+
+    Ok(crop_to_rect(&screenshot, left, top, width, height)?)
+}
@@ -1,13 +1,18 @@
-use serde_json::json;
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use thirtyfour::common::command::{Command, ExtensionCommand};
 use thirtyfour::prelude::*;
 
 use crate::config::constants::{
     APPIUM_PORT, APPIUM_SERVER_URL, DEVELOPMENT_TEAM, DEVICE_OS, DEVICE_UDID, HOST_OS, IDENTIFIER,
     IOS_VERSION,
 };
+use crate::services::native::AppTarget;
 use crate::setup::ensure::ensure_chromedriver;
 
-pub async fn create_webdriver(browser: &str) -> Result<WebDriver, String> {
+// `proxy_port` を指定すると端末の `localhost` のプロキシを経由させる（Firefox のみ）
+pub async fn create_webdriver(browser: &str, proxy_port: Option<u16>) -> Result<WebDriver, String> {
     let mut caps = Capabilities::new();
     caps.insert("browserName".to_string(), json!(browser));
 
@@ -24,6 +29,11 @@ pub async fn create_webdriver(browser: &str) -> Result<WebDriver, String> {
                 "appium:chromedriverExecutable".to_string(),
                 json!(chromedriver_str),
             );
+            // コンソールのログと通信（CDP の Network イベント）を `se/log` で取得できるようにする
+            caps.insert(
+                "goog:loggingPrefs".to_string(),
+                json!({ "browser": "ALL", "performance": "ALL" }),
+            );
         }
         "firefox" => {
             let host_os = match HOST_OS {
@@ -38,24 +48,18 @@ pub async fn create_webdriver(browser: &str) -> Result<WebDriver, String> {
                     "androidPackage": "org.mozilla.firefox",
                 }),
             );
+            if let Some(port) = proxy_port {
+                let proxy = format!("127.0.0.1:{}", port);
+                caps.insert(
+                    "proxy".to_string(),
+                    json!({ "proxyType": "manual", "httpProxy": proxy, "sslProxy": proxy }),
+                );
+            }
         }
         "safari" => {
-            caps.insert("platformName".to_string(), json!(DEVICE_OS));
-            caps.insert("port".to_string(), json!(APPIUM_PORT));
+            insert_ios_caps(&mut caps);
             caps.insert("startIWDP".to_string(), json!(true));
-            caps.insert("appium:udid".to_string(), json!(DEVICE_UDID));
-            caps.insert("appium:automationName".to_string(), json!("XCUITest"));
             caps.insert("appium:browserName".to_string(), json!(browser));
-            caps.insert("appium:deviceName".to_string(), json!("iPhone"));
-            caps.insert("appium:platformVersion".to_string(), json!(IOS_VERSION));
-            caps.insert("appium:noReset".to_string(), json!(true));
-            caps.insert("appium:xcodeOrgId".to_string(), json!(*DEVELOPMENT_TEAM));
-            caps.insert(
-                "appium:xcodeSigningId".to_string(),
-                json!("Developer ID Application"),
-            );
-            caps.insert("appium:updatedWDABundleId".to_string(), json!(IDENTIFIER));
-            caps.insert("appium:useNewWDA".to_string(), json!(true));
         }
         _ => return Err("Unsupported browser".to_string()),
     };
@@ -64,3 +68,122 @@ pub async fn create_webdriver(browser: &str) -> Result<WebDriver, String> {
         .await
         .map_err(|e| format!("Failed to start WebDriver: {}", e))
 }
+
+// アプリ（ネイティブ画面）を操作するセッション
+// アプリを指定しなければ、前面に表示されているアプリをそのまま操作する
+pub async fn create_app_driver(
+    app: &AppTarget,
+    android_serial: Option<&str>,
+) -> Result<WebDriver, String> {
+    let mut caps = Capabilities::new();
+    let os = DEVICE_OS.lock().unwrap().clone().unwrap_or_default();
+
+    match os.as_str() {
+        "Android" => {
+            caps.insert("platformName".to_string(), json!("Android"));
+            caps.insert("appium:automationName".to_string(), json!("UiAutomator2"));
+            caps.insert("appium:noReset".to_string(), json!(true));
+            if let Some(serial) = android_serial {
+                caps.insert("appium:udid".to_string(), json!(serial));
+            }
+            if let Some(package) = &app.app_package {
+                caps.insert("appium:appPackage".to_string(), json!(package));
+            }
+            if let Some(activity) = &app.app_activity {
+                caps.insert("appium:appActivity".to_string(), json!(activity));
+            }
+            // WebView に切り替えるときに使う
+            if let Some(chromedriver) = ensure_chromedriver()?.to_str() {
+                caps.insert(
+                    "appium:chromedriverExecutable".to_string(),
+                    json!(chromedriver),
+                );
+            }
+        }
+        "iOS" => {
+            insert_ios_caps(&mut caps);
+            if let Some(bundle_id) = &app.bundle_id {
+                caps.insert("appium:bundleId".to_string(), json!(bundle_id));
+            }
+        }
+        _ => return Err("No supported device connected".to_string()),
+    }
+
+    WebDriver::new(&*APPIUM_SERVER_URL, caps)
+        .await
+        .map_err(|e| format!("Failed to start WebDriver: {}", e))
+}
+
+// XCUITest（WebDriverAgent）の共通設定
+fn insert_ios_caps(caps: &mut Capabilities) {
+    caps.insert("platformName".to_string(), json!(DEVICE_OS));
+    caps.insert("port".to_string(), json!(APPIUM_PORT));
+    caps.insert("appium:udid".to_string(), json!(DEVICE_UDID));
+    caps.insert("appium:automationName".to_string(), json!("XCUITest"));
+    caps.insert("appium:deviceName".to_string(), json!("iPhone"));
+    caps.insert("appium:platformVersion".to_string(), json!(IOS_VERSION));
+    caps.insert("appium:noReset".to_string(), json!(true));
+    caps.insert("appium:xcodeOrgId".to_string(), json!(*DEVELOPMENT_TEAM));
+    caps.insert(
+        "appium:xcodeSigningId".to_string(),
+        json!("Developer ID Application"),
+    );
+    caps.insert("appium:updatedWDABundleId".to_string(), json!(IDENTIFIER));
+    caps.insert("appium:useNewWDA".to_string(), json!(true));
+}
+
+// thirtyfour にないエンドポイント（`/session/{id}` 以下）を呼び出すためのコマンド
+#[derive(Debug)]
+pub struct SessionCommand {
+    method: Method,
+    endpoint: String,
+    body: Option<Value>,
+}
+
+impl SessionCommand {
+    pub fn get(endpoint: &str) -> Self {
+        SessionCommand {
+            method: Method::GET,
+            endpoint: endpoint.to_string(),
+            body: None,
+        }
+    }
+
+    pub fn post(endpoint: &str, body: Value) -> Self {
+        SessionCommand {
+            method: Method::POST,
+            endpoint: endpoint.to_string(),
+            body: Some(body),
+        }
+    }
+
+    pub fn delete(endpoint: &str) -> Self {
+        SessionCommand {
+            method: Method::DELETE,
+            endpoint: endpoint.to_string(),
+            body: None,
+        }
+    }
+
+    // 実行してレスポンスの `value` を返す
+    pub async fn send(self, driver: &WebDriver) -> WebDriverResult<Value> {
+        driver
+            .cmd(Command::ExtensionCommand(Box::new(self)))
+            .await?
+            .value_json()
+    }
+}
+
+impl ExtensionCommand for SessionCommand {
+    fn parameters_json(&self) -> Option<Value> {
+        self.body.clone()
+    }
+
+    fn method(&self) -> Method {
+        self.method.clone()
+    }
+
+    fn endpoint(&self) -> Arc<str> {
+        Arc::from(self.endpoint.as_str())
+    }
+}
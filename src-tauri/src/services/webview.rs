@@ -0,0 +1,90 @@
+use log::{debug, info};
+use serde::Deserialize;
+use serde_json::json;
+use thirtyfour::prelude::*;
+use tokio::time::{sleep, Duration, Instant};
+
+use crate::config::constants::{DEVICE_OS, WEBVIEW_TIMEOUT};
+use crate::services::native::AppTarget;
+use crate::services::webrdiver::SessionCommand;
+
+// アプリ内の WebView（アプリ内ブラウザ、ハイブリッドアプリ）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WebviewTarget {
+    #[serde(flatten)]
+    pub app: AppTarget,
+    // 切り替える context 名（未指定なら最初の `WEBVIEW_*`）
+    pub context: Option<String>,
+    // URL をディープリンクとして開く（アプリ内ブラウザで開かせる）
+    pub deep_link: bool,
+}
+
+// Appium の context のうち `WEBVIEW_*` だけを返す
+pub async fn list_webview_contexts(driver: &WebDriver) -> Result<Vec<String>, String> {
+    let contexts = SessionCommand::get("contexts")
+        .send(driver)
+        .await
+        .map_err(|e| format!("Failed to list contexts: {}", e))?;
+    debug!("Contexts: {}", contexts);
+
+    Ok(contexts
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|context| context.as_str())
+        .filter(|context| context.starts_with("WEBVIEW_"))
+        .map(str::to_string)
+        .collect())
+}
+
+// WebView が現れるまで待ってから切り替え、切り替えた context 名を返す
+pub async fn switch_to_webview(
+    driver: &WebDriver,
+    target: &WebviewTarget,
+) -> Result<String, String> {
+    let start_time = Instant::now();
+    let context = loop {
+        let contexts = list_webview_contexts(driver).await?;
+        let found = match &target.context {
+            Some(name) => contexts.into_iter().find(|context| context == name),
+            None => contexts.into_iter().next(),
+        };
+        if let Some(context) = found {
+            break context;
+        }
+        if start_time.elapsed() >= WEBVIEW_TIMEOUT {
+            return Err(match &target.context {
+                Some(name) => format!("WebView context not found: {}", name),
+                None => "No WebView context found in the app".to_string(),
+            });
+        }
+        sleep(Duration::from_millis(500)).await;
+    };
+
+    SessionCommand::post("context", json!({ "name": context }))
+        .send(driver)
+        .await
+        .map_err(|e| format!("Failed to switch to {}: {}", context, e))?;
+    info!("Switched to {}", context);
+
+    Ok(context)
+}
+
+// URL をディープリンクとして開く（対応するアプリが開く）
+pub async fn open_deep_link(driver: &WebDriver, url: &str, app: &AppTarget) -> Result<(), String> {
+    info!("Opening deep link: {}", url);
+    let os = DEVICE_OS.lock().unwrap().clone().unwrap_or_default();
+
+    let args = if os == "Android" {
+        json!({ "url": url, "package": app.app_package })
+    } else {
+        json!({ "url": url, "bundleId": app.bundle_id })
+    };
+
+    driver
+        .execute("mobile: deepLink", vec![args])
+        .await
+        .map_err(|e| format!("Failed to open deep link: {}", e))?;
+    Ok(())
+}
@@ -1,8 +1,78 @@
 use log::{debug, info};
+use serde::Deserialize;
+use serde_json::{json, Value};
 use thirtyfour::prelude::*;
 use tokio::time::{sleep, Duration, Instant};
 
-use crate::config::constants::APPIUM_SERVER_URL;
+use crate::config::constants::{APPIUM_SERVER_URL, WAIT_STRATEGY_TIMEOUT};
+use crate::services::console::CONSOLE_TRACKER_SCRIPT;
+use crate::services::dom::{
+    freeze_page, Determinism, ElementTarget, HideMethod, HideRule, FIND_ELEMENTS_SCRIPT,
+};
+use crate::services::performance::PERFORMANCE_OBSERVER_SCRIPT;
+
+// 読み込み完了後に追加で待つ条件（組み合わせ可能）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WaitStrategy {
+    // fetch / XHR / リソース読み込みが `idle_ms` の間発生しない
+    NetworkIdle {
+        #[serde(default = "default_idle_ms")]
+        idle_ms: u64,
+    },
+    SelectorPresent {
+        target: ElementTarget,
+    },
+    SelectorAbsent {
+        target: ElementTarget,
+    },
+    // `document.fonts` の読み込み完了
+    FontsReady,
+    // 遅延読み込み以外の画像がすべてデコード済み
+    ImagesDecoded,
+    // 真を返す JavaScript（関数の本体として実行）
+    Predicate {
+        script: String,
+    },
+    Delay {
+        ms: u64,
+    },
+}
+
+fn default_idle_ms() -> u64 {
+    500
+}
+
+// fetch / XHR の実行中の数と最後の通信時刻を記録するトラッカー
+const NETWORK_TRACKER_SCRIPT: &str = r#"
+    if (!window.__scshokiNetwork) {
+        const tracker = { pending: 0, lastActivity: performance.now() };
+        window.__scshokiNetwork = tracker;
+        const start = () => { tracker.pending++; tracker.lastActivity = performance.now(); };
+        const end = () => { tracker.pending = Math.max(0, tracker.pending - 1); tracker.lastActivity = performance.now(); };
+
+        const originalFetch = window.fetch;
+        if (originalFetch) {
+            window.fetch = function(...args) {
+                start();
+                return originalFetch.apply(this, args).finally(end);
+            };
+        }
+
+        const originalSend = XMLHttpRequest.prototype.send;
+        XMLHttpRequest.prototype.send = function(...args) {
+            start();
+            this.addEventListener('loadend', end, { once: true });
+            return originalSend.apply(this, args);
+        };
+
+        // 画像やスクリプトなど fetch / XHR 以外の読み込みも最後の通信時刻に反映
+        if (window.PerformanceObserver) {
+            new PerformanceObserver(() => { tracker.lastActivity = performance.now(); })
+                .observe({ type: 'resource', buffered: false });
+        }
+    }
+"#;
 
 // Appiumが起動完了するまで `/status` をポーリング
 pub async fn wait_for_appium_ready(timeout: Duration) -> Result<(), String> {
@@ -27,7 +97,16 @@ pub async fn wait_for_appium_ready(timeout: Duration) -> Result<(), String> {
     Err("Timed out waiting for Appium to be ready".to_string())
 }
 
-pub async fn wait_for_page_load(driver: &WebDriver, url: &str) -> Result<(), String> {
+// 読み込み完了前に、必要であればページの動きを止める
+// 完了後は `strategies` を順番に待ち、タイムアウトしたものは警告として返す
+pub async fn wait_for_page_load(
+    driver: &WebDriver,
+    url: &str,
+    determinism: &Determinism,
+    strategies: &[WaitStrategy],
+    track_console: bool,
+    track_performance: bool,
+) -> Result<Vec<String>, String> {
     debug!("wait_for_page_load");
 
     let timeout = Duration::from_secs(10); // 最大10秒待つ
@@ -43,9 +122,39 @@ pub async fn wait_for_page_load(driver: &WebDriver, url: &str) -> Result<(), Str
             .ok_or("Failed to parse document.readyState")? // Noneならエラー
             .to_string(); // String に変換
 
+        // DOM ができたら時刻と乱数を固定し、完了時にもう一度アニメーション等を止める
+        if ready_state == "interactive" || ready_state == "complete" {
+            freeze_page(driver, determinism)
+                .await
+                .map_err(|e| format!("Failed to freeze page: {}", e))?;
+
+            // コンソールと通信の監視はできるだけ早く仕込む
+            if track_console {
+                driver
+                    .execute(CONSOLE_TRACKER_SCRIPT, vec![])
+                    .await
+                    .map_err(|e| format!("Failed to install console tracker: {}", e))?;
+            }
+            if track_performance {
+                driver
+                    .execute(PERFORMANCE_OBSERVER_SCRIPT, vec![])
+                    .await
+                    .map_err(|e| format!("Failed to install performance observer: {}", e))?;
+            }
+            if strategies
+                .iter()
+                .any(|s| matches!(s, WaitStrategy::NetworkIdle { .. }))
+            {
+                driver
+                    .execute(NETWORK_TRACKER_SCRIPT, vec![])
+                    .await
+                    .map_err(|e| format!("Failed to install network tracker: {}", e))?;
+            }
+        }
+
         if ready_state == "complete" {
             info!("[{}] is loaded.", url);
-            return Ok(());
+            return wait_for_strategies(driver, strategies).await;
         }
 
         sleep(Duration::from_millis(500)).await; // 0.5秒ごとに再チェック
@@ -54,6 +163,109 @@ pub async fn wait_for_page_load(driver: &WebDriver, url: &str) -> Result<(), Str
     Err("Timed out waiting for page to load".to_string())
 }
 
+// 追加の待機条件を順番に待つ
+async fn wait_for_strategies(
+    driver: &WebDriver,
+    strategies: &[WaitStrategy],
+) -> Result<Vec<String>, String> {
+    let mut warnings = vec![];
+
+    for strategy in strategies {
+        debug!("Waiting for {:?}", strategy);
+
+        let (script, args) = match strategy {
+            WaitStrategy::Delay { ms } => {
+                sleep(Duration::from_millis(*ms)).await;
+                continue;
+            }
+            WaitStrategy::NetworkIdle { idle_ms } => (
+                r#"
+                const tracker = window.__scshokiNetwork;
+                return !tracker
+                    || (tracker.pending === 0 && performance.now() - tracker.lastActivity >= arguments[0]);
+                "#
+                .to_string(),
+                vec![json!(idle_ms)],
+            ),
+            WaitStrategy::SelectorPresent { target } => (
+                format!(
+                    "{}\nreturn findElements(arguments[0]).length > 0;",
+                    FIND_ELEMENTS_SCRIPT
+                ),
+                vec![target.to_arg()],
+            ),
+            WaitStrategy::SelectorAbsent { target } => (
+                format!(
+                    "{}\nreturn findElements(arguments[0]).length === 0;",
+                    FIND_ELEMENTS_SCRIPT
+                ),
+                vec![target.to_arg()],
+            ),
+            WaitStrategy::FontsReady => (
+                "return !document.fonts || document.fonts.status === 'loaded';".to_string(),
+                vec![],
+            ),
+            WaitStrategy::ImagesDecoded => (
+                // `complete` は読み込み前や失敗時も true になるため `decode()` の完了を待つ（失敗した画像は無視）
+                r#"
+                if (!window.__scshokiImagesDecoding) {
+                    window.__scshokiImagesDecoded = false;
+                    window.__scshokiImagesDecoding = Promise.all(
+                        Array.from(document.images)
+                            .filter(img => img.loading !== 'lazy')
+                            .map(img => img.decode().catch(() => {}))
+                    ).then(() => { window.__scshokiImagesDecoded = true; });
+                }
+                return window.__scshokiImagesDecoded;
+                "#
+                .to_string(),
+                vec![],
+            ),
+            WaitStrategy::Predicate { script } => (script.clone(), vec![]),
+        };
+
+        match poll_until_truthy(driver, &script, args, WAIT_STRATEGY_TIMEOUT).await {
+            Ok(true) => info!("Wait condition satisfied: {:?}", strategy),
+            Ok(false) => warnings.push(format!("Timed out waiting for {:?}", strategy)),
+            Err(e) => warnings.push(format!("Failed to wait for {:?}: {}", strategy, e)),
+        }
+    }
+
+    Ok(warnings)
+}
+
+// スクリプトが真を返すまでポーリング（タイムアウト時は false）
+async fn poll_until_truthy(
+    driver: &WebDriver,
+    script: &str,
+    args: Vec<Value>,
+    timeout: Duration,
+) -> Result<bool, String> {
+    let start_time = Instant::now();
+
+    while start_time.elapsed() < timeout {
+        let result = driver
+            .execute(script, args.clone())
+            .await
+            .map_err(|e| format!("Failed to execute script: {}", e))?;
+
+        let truthy = match result.json() {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+            Value::String(s) => !s.is_empty(),
+            _ => true,
+        };
+        if truthy {
+            return Ok(true);
+        }
+
+        sleep(Duration::from_millis(200)).await; // 200msごとに再チェック
+    }
+
+    Ok(false)
+}
+
 pub async fn wait_for_scroll_complete(driver: &WebDriver) -> Result<(), String> {
     let timeout = std::time::Duration::from_secs(5); // 最大5秒待つ
     let start_time = std::time::Instant::now();
@@ -79,21 +291,31 @@ pub async fn wait_for_scroll_complete(driver: &WebDriver) -> Result<(), String>
     Err("Timed out waiting for scroll to complete".to_string())
 }
 
-pub async fn wait_for_elements_hidden(driver: &WebDriver, selectors: &str) -> Result<(), String> {
+// 一致する要素がない場合は `every` が true を返すので待たずに終了する
+pub async fn wait_for_elements_hidden(driver: &WebDriver, rule: &HideRule) -> Result<(), String> {
     let timeout = std::time::Duration::from_secs(5); // 最大5秒待つ
     let start_time = std::time::Instant::now();
 
+    // 非表示の方法に応じて判定するプロパティを切り替える
+    let condition = match rule.method {
+        HideMethod::Visibility => r#"style.visibility === "hidden""#,
+        HideMethod::Display => r#"style.display === "none""#,
+        HideMethod::Opacity => r#"style.opacity === "0""#,
+    };
     let script = format!(
         r#"
-        return Array.from(document.querySelectorAll("{}"))
-            .every(e => getComputedStyle(e).visibility === "hidden");
+        {}
+        return findElements(arguments[0]).every(e => {{
+            const style = e.ownerDocument.defaultView.getComputedStyle(e);
+            return {};
+        }});
         "#,
-        selectors
+        FIND_ELEMENTS_SCRIPT, condition
     );
 
     while start_time.elapsed() < timeout {
         let result = driver
-            .execute(&script, vec![])
+            .execute(&script, vec![rule.target.to_arg()])
             .await
             .map_err(|e| format!("Failed to check element visibility: {}", e))?
             .json()
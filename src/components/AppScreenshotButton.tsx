@@ -0,0 +1,58 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ScreenshotResult } from "./ScreenshotButton";
+
+// Rust側の `NativeCaptureOptions` に対応（アプリを指定しなければ前面のアプリを撮影）
+export interface NativeCaptureOptions {
+    appPackage?: string;
+    appActivity?: string;
+    bundleId?: string;
+    androidSerial?: string;
+    maxSwipes?: number;
+}
+
+export default function AppScreenshotButton() {
+    const [appId, setAppId] = useState("");
+    const [status, setStatus] = useState<string | null>(null);
+    const [warnings, setWarnings] = useState<string[]>([]);
+
+    const handleScreenshot = async () => {
+        setStatus("アプリの画面を取得中...");
+        setWarnings([]);
+
+        // `com.example.app` は Android のパッケージ名と iOS の Bundle ID の両方として渡す
+        const options: NativeCaptureOptions = appId ? { appPackage: appId, bundleId: appId } : {};
+
+        try {
+            const response = await invoke<ScreenshotResult>("take_app_screenshot", { options });
+            setStatus(`スクリーンショットを保存しました: ${response.path}`);
+            setWarnings(response.warnings);
+        } catch (error) {
+            setStatus(`エラー: ${error}`);
+        }
+    };
+
+    return (
+        <div className="mb-4">
+            <label className="block text-sm font-medium text-gray-700">アプリ画面の撮影</label>
+            <input
+                type="text"
+                value={appId}
+                onChange={(e) => setAppId(e.target.value)}
+                className="mt-1 block w-full p-2 border border-gray-300 rounded-md"
+                placeholder="パッケージ名 / Bundle ID（省略時は表示中のアプリ）"
+            />
+            <button type="button" onClick={handleScreenshot} className="mt-2 px-4 py-2 bg-blue-500 text-white rounded">
+                アプリ画面を撮る
+            </button>
+            {status && <p className="mt-2 text-sm">{status}</p>}
+            {warnings.length > 0 && (
+                <ul className="mt-2 text-sm text-yellow-700">
+                    {warnings.map((warning, index) => (
+                        <li key={index}>{warning}</li>
+                    ))}
+                </ul>
+            )}
+        </div>
+    );
+}
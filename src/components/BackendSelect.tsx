@@ -0,0 +1,66 @@
+// src/components/BackendSelect.tsx
+export type CaptureBackend = "appium" | "cdp_adb" | "webview";
+
+interface BackendSelectProps {
+    allowCdp: boolean;
+    backend: CaptureBackend;
+    setBackend: (backend: CaptureBackend) => void;
+    androidSerial: string;
+    setAndroidSerial: (serial: string) => void;
+    useScreencap: boolean;
+    setUseScreencap: (useScreencap: boolean) => void;
+}
+
+// Android Chrome のみ、Appium を使わず DevTools に直接接続できる
+// アプリ内WebView は起動中のアプリの WebView に切り替えて撮影する
+export default function BackendSelect({
+    allowCdp,
+    backend,
+    setBackend,
+    androidSerial,
+    setAndroidSerial,
+    useScreencap,
+    setUseScreencap,
+}: BackendSelectProps) {
+    const backends = [
+        { value: "appium", label: "Appium" },
+        { value: "cdp_adb", label: "DevTools（ADB）" },
+        { value: "webview", label: "アプリ内WebView" },
+    ].filter(({ value }) => allowCdp || value !== "cdp_adb") as { value: CaptureBackend; label: string }[];
+
+    return (
+        <div className="mb-4">
+            <label className="block text-sm font-medium text-gray-700">接続方法</label>
+            <div className="mt-2 flex space-x-4">
+                {backends.map(({ value, label }) => (
+                    <button
+                        key={value}
+                        type="button"
+                        onClick={() => setBackend(value)}
+                        className={`p-2 border rounded-md ${backend === value ? "bg-blue-500 text-white" : "bg-gray-100"}`}
+                    >
+                        {label}
+                    </button>
+                ))}
+            </div>
+            {backend === "appium" && allowCdp && (
+                <label className="mt-2 block text-sm font-medium text-gray-700">
+                    <input
+                        type="checkbox"
+                        checked={useScreencap}
+                        onChange={() => setUseScreencap(!useScreencap)}
+                        className="mr-2"
+                    />
+                    adb screencap で撮影（高速）
+                </label>
+            )}
+            <input
+                type="text"
+                value={androidSerial}
+                onChange={(e) => setAndroidSerial(e.target.value)}
+                className="mt-2 block w-full p-2 border border-gray-300 rounded-md"
+                placeholder="端末のシリアル（省略可）"
+            />
+        </div>
+    );
+}
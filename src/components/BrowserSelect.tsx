@@ -4,7 +4,7 @@ interface BrowserSelectProps {
 }
 
 export default function BrowserSelect({ selectedBrowser, setSelectedBrowser }: BrowserSelectProps) {
-    const browsers = ["Chrome", "Firefox", "Edge", "Safari"];
+    const browsers = ["Chrome", "Firefox", "Safari"];
 
     return (
         <div className="mb-4">
@@ -0,0 +1,179 @@
+import { useState } from "react";
+import { CaptureOptions, ConsentAction } from "./ScreenshotButton";
+
+// チェックボックスで切り替える出力と機能
+export interface CaptureToggles {
+    consoleLog: boolean;
+    networkLog: boolean;
+    domSnapshot: boolean;
+    pdf: boolean;
+    audit: boolean;
+    blocking: boolean;
+    consent: boolean;
+    consentAction: ConsentAction;
+    recording: boolean;
+    performanceMetrics: boolean;
+    determinism: boolean;
+    tileValidation: boolean;
+    scrollDriver: "script" | "touch" | "mobile_scroll";
+}
+
+export const defaultToggles: CaptureToggles = {
+    consoleLog: false,
+    networkLog: false,
+    domSnapshot: false,
+    pdf: false,
+    audit: false,
+    blocking: false,
+    consent: false,
+    consentAction: "accept",
+    recording: false,
+    performanceMetrics: false,
+    determinism: false,
+    tileValidation: false,
+    scrollDriver: "script",
+};
+
+// JSON で指定した詳細設定にチェックボックスの状態を重ねる（詳細設定の他の項目は残す）
+export function mergeCaptureOptions(advanced: Partial<CaptureOptions>, toggles: CaptureToggles): Partial<CaptureOptions> {
+    return {
+        ...advanced,
+        consoleLog: toggles.consoleLog,
+        networkLog: toggles.networkLog,
+        domSnapshot: toggles.domSnapshot,
+        pdf: { ...advanced.pdf, enabled: toggles.pdf },
+        audit: { ...advanced.audit, enabled: toggles.audit },
+        blocking: { ...advanced.blocking, enabled: toggles.blocking },
+        consent: { ...advanced.consent, enabled: toggles.consent, action: toggles.consentAction },
+        recording: { ...advanced.recording, enabled: toggles.recording },
+        performanceMetrics: toggles.performanceMetrics,
+        determinism: { ...advanced.determinism, enabled: toggles.determinism },
+        tileValidation: { ...advanced.tileValidation, enabled: toggles.tileValidation },
+        scrollDriver: toggles.scrollDriver,
+    };
+}
+
+interface CaptureOptionsFormProps {
+    toggles: CaptureToggles;
+    setToggles: (toggles: CaptureToggles) => void;
+    setAdvancedOptions: (options: Partial<CaptureOptions>) => void;
+}
+
+type BooleanToggle = Exclude<keyof CaptureToggles, "consentAction" | "scrollDriver">;
+
+const outputs: { key: BooleanToggle; label: string }[] = [
+    { key: "consoleLog", label: "コンソールログ（.console.json）" },
+    { key: "networkLog", label: "通信ログ（.har）" },
+    { key: "domSnapshot", label: "DOM・テキスト（.html / .txt）" },
+    { key: "pdf", label: "PDF" },
+    { key: "audit", label: "アクセシビリティ監査（.a11y.json / .a11y.png）" },
+    { key: "performanceMetrics", label: "パフォーマンス（.metrics.json）" },
+    { key: "recording", label: "画面録画（.mp4）" },
+];
+
+const features: { key: BooleanToggle; label: string }[] = [
+    { key: "blocking", label: "広告・トラッカーをブロック" },
+    { key: "consent", label: "Cookie同意バナーを閉じる" },
+    { key: "determinism", label: "アニメーション・時刻・乱数を固定" },
+    { key: "tileValidation", label: "描画途中のスクリーンショットを撮り直す" },
+];
+
+const consentActions: { value: ConsentAction; label: string }[] = [
+    { value: "accept", label: "同意する" },
+    { value: "reject", label: "拒否する" },
+    { value: "hide", label: "非表示にする" },
+];
+
+const scrollDrivers: { value: CaptureToggles["scrollDriver"]; label: string }[] = [
+    { value: "script", label: "スクリプト" },
+    { value: "touch", label: "タッチ操作" },
+    { value: "mobile_scroll", label: "Appium（mobile: scroll）" },
+];
+
+export default function CaptureOptionsForm({ toggles, setToggles, setAdvancedOptions }: CaptureOptionsFormProps) {
+    const [json, setJson] = useState("");
+    const [jsonError, setJsonError] = useState<string | null>(null);
+
+    // マスク・ハイライト・フック・待機条件などは JSON で指定する
+    const handleJson = (value: string) => {
+        setJson(value);
+        if (!value.trim()) {
+            setAdvancedOptions({});
+            setJsonError(null);
+            return;
+        }
+        try {
+            const parsed = JSON.parse(value);
+            if (typeof parsed !== "object" || parsed === null || Array.isArray(parsed)) {
+                throw new Error("オブジェクトを指定してください");
+            }
+            setAdvancedOptions(parsed);
+            setJsonError(null);
+        } catch (error) {
+            setJsonError(`${error}`);
+        }
+    };
+
+    const checkbox = ({ key, label }: { key: BooleanToggle; label: string }) => (
+        <label key={key} className="block text-sm">
+            <input
+                type="checkbox"
+                checked={toggles[key]}
+                onChange={() => setToggles({ ...toggles, [key]: !toggles[key] })}
+                className="mr-2"
+            />
+            {label}
+        </label>
+    );
+
+    return (
+        <div className="mb-4">
+            <label className="block text-sm font-medium text-gray-700">一緒に保存するもの</label>
+            <div className="mt-2">{outputs.map(checkbox)}</div>
+
+            <label className="mt-4 block text-sm font-medium text-gray-700">撮影の設定</label>
+            <div className="mt-2">{features.map(checkbox)}</div>
+            {toggles.consent && (
+                <select
+                    value={toggles.consentAction}
+                    onChange={(e) => setToggles({ ...toggles, consentAction: e.target.value as ConsentAction })}
+                    className="mt-1 p-2 border rounded"
+                >
+                    {consentActions.map((a) => (
+                        <option key={a.value} value={a.value}>
+                            {a.label}
+                        </option>
+                    ))}
+                </select>
+            )}
+            <label className="mt-2 block text-sm">
+                スクロール方法
+                <select
+                    value={toggles.scrollDriver}
+                    onChange={(e) =>
+                        setToggles({ ...toggles, scrollDriver: e.target.value as CaptureToggles["scrollDriver"] })
+                    }
+                    className="ml-2 p-2 border rounded"
+                >
+                    {scrollDrivers.map((d) => (
+                        <option key={d.value} value={d.value}>
+                            {d.label}
+                        </option>
+                    ))}
+                </select>
+            </label>
+
+            <label className="mt-4 block text-sm font-medium text-gray-700">
+                詳細設定（JSON: maskRules, textRedaction, highlightRules, hooks, waitStrategies, stateCaptures など）
+            </label>
+            <textarea
+                value={json}
+                onChange={(e) => handleJson(e.target.value)}
+                rows={6}
+                className="mt-1 block w-full p-2 border border-gray-300 rounded-md font-mono text-xs"
+                placeholder={'{\n  "maskRules": [{ "selector": ".user-name", "kind": "css", "effect": { "type": "blur", "sigma": 8 } }],\n  "waitStrategies": [{ "type": "fonts_ready" }]\n}'}
+            />
+            {jsonError && <p className="mt-1 text-sm text-red-600">{jsonError}</p>}
+        </div>
+    );
+}
@@ -1,19 +1,140 @@
+export type HidePolicy = "always" | "after_first" | "last_only" | "never_in_output";
+export type HideMethod = "visibility" | "display" | "opacity";
+export type SelectorKind = "css" | "xpath" | "text";
+
+// Rust側の `ElementTarget` に対応
+export interface ElementTarget {
+    selector: string;
+    kind: SelectorKind;
+    pierceShadow: boolean;
+    includeFrames: boolean;
+}
+
+export interface HideRule extends ElementTarget {
+    policy: HidePolicy;
+    method: HideMethod;
+}
+
 interface HiddenElementsFormProps {
-    hiddenElements: string;
-    setHiddenElements: (value: string) => void;
+    hiddenElements: HideRule[];
+    setHiddenElements: (value: HideRule[]) => void;
 }
 
+const kinds: { value: SelectorKind; label: string }[] = [
+    { value: "css", label: "CSS" },
+    { value: "xpath", label: "XPath" },
+    { value: "text", label: "テキスト" },
+];
+
+const policies: { value: HidePolicy; label: string }[] = [
+    { value: "always", label: "最初から非表示" },
+    { value: "after_first", label: "2枚目以降で非表示" },
+    { value: "last_only", label: "最後だけ表示" },
+    { value: "never_in_output", label: "出力に含めない" },
+];
+
+const methods: { value: HideMethod; label: string }[] = [
+    { value: "visibility", label: "visibility: hidden" },
+    { value: "display", label: "display: none" },
+    { value: "opacity", label: "opacity: 0" },
+];
+
 export default function HiddenElementsForm({ hiddenElements, setHiddenElements }: HiddenElementsFormProps) {
+    const updateRule = (index: number, rule: Partial<HideRule>) => {
+        setHiddenElements(hiddenElements.map((r, i) => (i === index ? { ...r, ...rule } : r)));
+    };
+
+    const addRule = () => {
+        setHiddenElements([
+            ...hiddenElements,
+            {
+                selector: "",
+                kind: "css",
+                pierceShadow: false,
+                includeFrames: false,
+                policy: "after_first",
+                method: "visibility",
+            },
+        ]);
+    };
+
+    const removeRule = (index: number) => {
+        setHiddenElements(hiddenElements.filter((_, i) => i !== index));
+    };
+
     return (
-        <label className="block">
-            非表示にする要素のセレクタ（例: `.header, .ad-banner`）:
-            <input
-                type="text"
-                value={hiddenElements}
-                onChange={(e) => setHiddenElements(e.target.value)}
-                placeholder=".header, .sticky, .ads"
-                className="mt-1 p-2 border rounded w-full"
-            />
-        </label>
+        <div className="mb-4">
+            <label className="block text-sm font-medium text-gray-700">
+                非表示にする要素のセレクタ（例: `.header, .ad-banner`）
+            </label>
+            {hiddenElements.map((rule, index) => (
+                <div key={index} className="mt-2 flex space-x-2">
+                    <select
+                        value={rule.kind}
+                        onChange={(e) => updateRule(index, { kind: e.target.value as SelectorKind })}
+                        className="p-2 border rounded"
+                    >
+                        {kinds.map((k) => (
+                            <option key={k.value} value={k.value}>
+                                {k.label}
+                            </option>
+                        ))}
+                    </select>
+                    <input
+                        type="text"
+                        value={rule.selector}
+                        onChange={(e) => updateRule(index, { selector: e.target.value })}
+                        placeholder=".header, .sticky, .ads"
+                        className="flex-1 p-2 border rounded"
+                    />
+                    <select
+                        value={rule.policy}
+                        onChange={(e) => updateRule(index, { policy: e.target.value as HidePolicy })}
+                        className="p-2 border rounded"
+                    >
+                        {policies.map((p) => (
+                            <option key={p.value} value={p.value}>
+                                {p.label}
+                            </option>
+                        ))}
+                    </select>
+                    <select
+                        value={rule.method}
+                        onChange={(e) => updateRule(index, { method: e.target.value as HideMethod })}
+                        className="p-2 border rounded"
+                    >
+                        {methods.map((m) => (
+                            <option key={m.value} value={m.value}>
+                                {m.label}
+                            </option>
+                        ))}
+                    </select>
+                    <label className="text-sm">
+                        <input
+                            type="checkbox"
+                            checked={rule.pierceShadow}
+                            onChange={() => updateRule(index, { pierceShadow: !rule.pierceShadow })}
+                            className="mr-1"
+                        />
+                        Shadow DOM
+                    </label>
+                    <label className="text-sm">
+                        <input
+                            type="checkbox"
+                            checked={rule.includeFrames}
+                            onChange={() => updateRule(index, { includeFrames: !rule.includeFrames })}
+                            className="mr-1"
+                        />
+                        iframe
+                    </label>
+                    <button type="button" onClick={() => removeRule(index)} className="px-2 border rounded">
+                        削除
+                    </button>
+                </div>
+            ))}
+            <button type="button" onClick={addRule} className="mt-2 px-2 py-1 border rounded">
+                ルールを追加
+            </button>
+        </div>
     );
 }
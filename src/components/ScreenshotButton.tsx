@@ -1,33 +1,248 @@
 import { useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { CaptureBackend } from "./BackendSelect";
+import { ElementTarget, HideRule } from "./HiddenElementsForm";
+import { WebviewTarget } from "./WebviewForm";
+
+// Rust側の `MaskEffect` に対応
+export type MaskEffect =
+    | { type: "fill"; color: string }
+    | { type: "pixelate"; block_size: number }
+    | { type: "blur"; sigma: number };
+
+export interface MaskRule extends ElementTarget {
+    effect: MaskEffect;
+}
+
+// Rust側の `HighlightRule` に対応（color は `#rrggbb`）
+export interface HighlightRule extends ElementTarget {
+    color?: string;
+    label?: boolean;
+}
+
+export type RedactionPreset = "email" | "phone" | "card_number";
+
+// Rust側の `TextRedaction` に対応（patterns は JavaScript の正規表現）
+export interface TextRedaction {
+    presets: RedactionPreset[];
+    patterns: string[];
+    effect?: MaskEffect;
+}
+
+// Rust側の `InjectionHook` に対応
+export interface InjectionHook {
+    point: "after_load" | "before_first_tile" | "before_each_tile";
+    css?: string;
+    js?: string;
+}
+
+// Rust側の `Determinism` に対応
+export interface Determinism {
+    enabled: boolean;
+    seed?: number;
+    fixedTime?: number;
+}
+
+// Rust側の `WaitStrategy` に対応
+export type WaitStrategy =
+    | { type: "network_idle"; idle_ms?: number }
+    | { type: "selector_present"; target: ElementTarget }
+    | { type: "selector_absent"; target: ElementTarget }
+    | { type: "fonts_ready" }
+    | { type: "images_decoded" }
+    | { type: "predicate"; script: string }
+    | { type: "delay"; ms: number };
+
+// Rust側の `CaptureOptions` に対応
+export interface CaptureOptions {
+    hiddenElements: HideRule[];
+    maskRules?: MaskRule[];
+    textRedaction?: TextRedaction;
+    highlightRules?: HighlightRule[];
+    hooks?: InjectionHook[];
+    determinism?: Determinism;
+    waitStrategies?: WaitStrategy[];
+    scrollDriver?: "script" | "touch" | "mobile_scroll";
+    browser?: string;
+    backend?: CaptureBackend;
+    androidSerial?: string;
+    tileSource?: "webdriver" | "adb_screencap";
+    tileValidation?: TileValidation;
+    stateCaptures?: StateCapture[];
+    webview?: WebviewTarget;
+    pdf?: PdfOptions;
+    consoleLog?: boolean;
+    networkLog?: boolean;
+    domSnapshot?: boolean;
+    audit?: AuditOptions;
+    blocking?: RequestBlocking;
+    consent?: ConsentOptions;
+    recording?: RecordingOptions;
+    performanceMetrics?: boolean;
+}
+
+// Rust側の `RecordingOptions` に対応
+export interface RecordingOptions {
+    enabled: boolean;
+    bitRate?: number;
+    timeLimit?: number;
+}
+
+// Rust側の `ConsentOptions` に対応
+export interface ConsentOptions {
+    enabled: boolean;
+    action?: ConsentAction;
+    rules?: ConsentRule[];
+}
+
+export type ConsentAction = "accept" | "reject" | "hide";
+
+// Rust側の `ConsentRule` に対応（各値は CSS セレクター）
+export interface ConsentRule {
+    name: string;
+    detect: string;
+    accept?: string;
+    reject?: string;
+    hide?: string[];
+}
+
+// Rust側の `RequestBlocking` に対応（パターンは EasyList 形式）
+export interface RequestBlocking {
+    enabled: boolean;
+    bundledList?: boolean;
+    patterns?: string[];
+    // iPhone の Wi-Fi プロキシから使う場合に LAN でも待ち受ける（Safari のみ）
+    allowLan?: boolean;
+}
+
+// Rust側の `AuditOptions` に対応
+export interface AuditOptions {
+    enabled: boolean;
+    tags?: string[];
+}
+
+// Rust側の `PdfOptions` に対応（単位は cm）
+export interface PdfOptions {
+    enabled: boolean;
+    pageSize?:
+        | { type: "a4" }
+        | { type: "a3" }
+        | { type: "letter" }
+        | { type: "legal" }
+        | { type: "custom"; width: number; height: number };
+    margin?: number;
+    scale?: number;
+    orientation?: "portrait" | "landscape";
+    background?: boolean;
+}
+
+// Rust側の `StateCapture` に対応
+export interface StateCapture extends ElementTarget {
+    states: ("default" | "hover" | "focus" | "active" | "expanded")[];
+    padding?: number;
+}
+
+// Rust側の `TileValidation` に対応
+export interface TileValidation {
+    enabled: boolean;
+    maxRetries?: number;
+    settleMs?: number;
+    spinner?: ElementTarget;
+}
+
+// Rust側の `ScreenshotResult` に対応
+export interface ScreenshotResult {
+    path: string;
+    pdfPath?: string;
+    videoPath?: string;
+    consoleSummary?: ConsoleSummary;
+    harPath?: string;
+    domSnapshotPath?: string;
+    metricsPath?: string;
+    auditPath?: string;
+    auditImagePath?: string;
+    auditSummary?: AuditSummary;
+    warnings: string[];
+    redactedMatches: number;
+    captureMethod: "native" | "stitched";
+    tileTiming?: TileTiming;
+    tileRetries: number;
+    stateSets: string[];
+    blockedRequests?: number;
+    consent?: { rule: string; action: ConsentAction };
+}
+
+// Rust側の `ConsoleSummary` に対応
+export interface ConsoleSummary {
+    total: number;
+    errors: number;
+    warnings: number;
+    exceptions: number;
+    unhandledRejections: number;
+}
+
+// Rust側の `AuditSummary` に対応
+export interface AuditSummary {
+    violations: number;
+    critical: number;
+    serious: number;
+    moderate: number;
+    minor: number;
+}
+
+// Rust側の `TileTiming` に対応
+export interface TileTiming {
+    tiles: number;
+    screencapMs: number;
+    estimatedWebdriverMs: number;
+}
 
 interface ScreenshotButtonProps {
     url: string;
-    hiddenElements: string; // 非表示にする要素のセレクタを追加
+    options: CaptureOptions;
 }
 
-export default function ScreenshotButton({ url, hiddenElements }: ScreenshotButtonProps) {
+export default function ScreenshotButton({ url, options }: ScreenshotButtonProps) {
     const [status, setStatus] = useState<string | null>(null);
+    const [warnings, setWarnings] = useState<string[]>([]);
 
     const handleScreenshot = async () => {
-        if (!url) {
+        // WebView は URL がなければ表示中のページをそのまま撮る
+        if (!url && options.backend !== "webview") {
             setStatus("URLを入力してください");
             return;
         }
 
         setStatus("スクリーンショットを取得中...");
+        setWarnings([]);
 
         try {
-            const response = await invoke<{ success: boolean; path: string; error?: string }>(
-                "take_screenshot",
-                { url, hiddenElements }
+            const response = await invoke<ScreenshotResult>("take_screenshot", { url, options });
+            setStatus(
+                `スクリーンショットを保存しました: ${response.path}` +
+                    (response.consoleSummary && response.consoleSummary.errors > 0
+                        ? `（JSエラー: ${response.consoleSummary.errors}件、うち例外${response.consoleSummary.exceptions}件・未処理のPromise${response.consoleSummary.unhandledRejections}件）`
+                        : "") +
+                    (response.auditSummary
+                        ? `（アクセシビリティ違反: ${response.auditSummary.violations}件、重大${response.auditSummary.critical}・深刻${response.auditSummary.serious}・中${response.auditSummary.moderate}・軽微${response.auditSummary.minor}箇所）`
+                        : "") +
+                    (response.consent ? `（同意バナー: ${response.consent.rule}）` : "") +
+                    (response.blockedRequests != null
+                        ? `（${response.blockedRequests}件の通信をブロック）`
+                        : "") +
+                    (response.harPath ? `（HAR: ${response.harPath}）` : "") +
+                    (response.metricsPath ? `（パフォーマンス: ${response.metricsPath}）` : "") +
+                    (response.pdfPath ? `（PDF: ${response.pdfPath}）` : "") +
+                    (response.videoPath ? `（録画: ${response.videoPath}）` : "") +
+                    (response.captureMethod === "native" ? "（一括撮影）" : "（スクロール結合）") +
+                    (response.tileTiming
+                        ? `（adb screencap で約${((response.tileTiming.estimatedWebdriverMs - response.tileTiming.screencapMs) / 1000).toFixed(1)}秒短縮）`
+                        : "") +
+                    (response.stateSets.length > 0 ? `（状態別: ${response.stateSets.length}件）` : "") +
+                    (response.tileRetries > 0 ? `（${response.tileRetries}回撮り直し）` : "") +
+                    (response.redactedMatches > 0 ? `（${response.redactedMatches}件のテキストを塗りつぶし）` : "")
             );
-
-            if (response.success) {
-                setStatus(`スクリーンショットを保存しました: ${response.path}`);
-            } else {
-                setStatus(`エラー: ${response.error}`);
-            }
+            setWarnings(response.warnings);
         } catch (error) {
             setStatus(`エラー: ${error}`);
         }
@@ -39,6 +254,13 @@ export default function ScreenshotButton({ url, hiddenElements }: ScreenshotButt
                 スクリーンショットを撮る
             </button>
             {status && <p className="mt-2 text-sm">{status}</p>}
+            {warnings.length > 0 && (
+                <ul className="mt-2 text-sm text-yellow-700">
+                    {warnings.map((warning, index) => (
+                        <li key={index}>{warning}</li>
+                    ))}
+                </ul>
+            )}
         </div>
     );
 }
@@ -0,0 +1,81 @@
+// src/components/WebviewForm.tsx
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+// Rust側の `WebviewTarget` に対応
+export interface WebviewTarget {
+    appPackage?: string;
+    appActivity?: string;
+    bundleId?: string;
+    context?: string;
+    deepLink: boolean;
+}
+
+interface WebviewFormProps {
+    webview: WebviewTarget;
+    setWebview: (webview: WebviewTarget) => void;
+    androidSerial?: string;
+}
+
+export default function WebviewForm({ webview, setWebview, androidSerial }: WebviewFormProps) {
+    const [contexts, setContexts] = useState<string[]>([]);
+    const [status, setStatus] = useState<string | null>(null);
+    const appId = webview.appPackage ?? webview.bundleId ?? "";
+
+    const handleList = async () => {
+        setStatus("WebViewを検索中...");
+        try {
+            const found = await invoke<string[]>("list_webviews", { target: webview, androidSerial });
+            setContexts(found);
+            setStatus(found.length > 0 ? null : "WebViewが見つかりません");
+        } catch (error) {
+            setStatus(`エラー: ${error}`);
+        }
+    };
+
+    return (
+        <div className="mb-4">
+            <label className="block text-sm font-medium text-gray-700">アプリ内WebView</label>
+            <input
+                type="text"
+                value={appId}
+                onChange={(e) =>
+                    setWebview({
+                        ...webview,
+                        appPackage: e.target.value || undefined,
+                        bundleId: e.target.value || undefined,
+                    })
+                }
+                className="mt-1 block w-full p-2 border border-gray-300 rounded-md"
+                placeholder="パッケージ名 / Bundle ID（省略時は表示中のアプリ）"
+            />
+            <label className="mt-2 block text-sm font-medium text-gray-700">
+                <input
+                    type="checkbox"
+                    checked={webview.deepLink}
+                    onChange={() => setWebview({ ...webview, deepLink: !webview.deepLink })}
+                    className="mr-2"
+                />
+                URLをディープリンクとして開く
+            </label>
+            <div className="mt-2 flex space-x-2">
+                <button type="button" onClick={handleList} className="p-2 border rounded-md bg-gray-100">
+                    WebView一覧
+                </button>
+                <select
+                    value={webview.context ?? ""}
+                    onChange={(e) => setWebview({ ...webview, context: e.target.value || undefined })}
+                    className="p-2 border border-gray-300 rounded-md"
+                >
+                    <option value="">最初のWebView</option>
+                    {contexts.map((context) => (
+                        <option key={context} value={context}>
+                            {context}
+                        </option>
+                    ))}
+                </select>
+            </div>
+            {status && <p className="mt-2 text-sm">{status}</p>}
+        </div>
+    );
+}
@@ -1,17 +1,32 @@
 import { useState } from "react";
 import UrlInputForm from "../components/UrlInputForm";
 import BasicAuthForm from "../components/BasicAuthForm";
-import HiddenElementsForm from "../components/HiddenElementsForm";
+import HiddenElementsForm, { HideRule } from "../components/HiddenElementsForm";
 import BrowserSelect from "../components/BrowserSelect";
-import ScreenshotButton from "../components/ScreenshotButton";
+import BackendSelect, { CaptureBackend } from "../components/BackendSelect";
+import WebviewForm, { WebviewTarget } from "../components/WebviewForm";
+import CaptureOptionsForm, {
+    CaptureToggles,
+    defaultToggles,
+    mergeCaptureOptions,
+} from "../components/CaptureOptionsForm";
+import ScreenshotButton, { CaptureOptions } from "../components/ScreenshotButton";
+import AppScreenshotButton from "../components/AppScreenshotButton";
 
 export default function Home() {
     const [url, setUrl] = useState("");
     const [useAuth, setUseAuth] = useState(false);
     const [username, setUsername] = useState("");
     const [password, setPassword] = useState("");
-    const [hiddenElements, setHiddenElements] = useState("");
-    const [selectedBrowser, setSelectedBrowser] = useState("Chrome");
+    const [hiddenElements, setHiddenElements] = useState<HideRule[]>([]);
+    const [selectedBrowser, setSelectedBrowser] = useState("Safari");
+    const [backend, setBackend] = useState<CaptureBackend>("appium");
+    const [androidSerial, setAndroidSerial] = useState("");
+    const [useScreencap, setUseScreencap] = useState(false);
+    const [webview, setWebview] = useState<WebviewTarget>({ deepLink: false });
+    const [toggles, setToggles] = useState<CaptureToggles>(defaultToggles);
+    const [advancedOptions, setAdvancedOptions] = useState<Partial<CaptureOptions>>({});
+    const isChrome = selectedBrowser === "Chrome";
 
     const handleSubmit = (e: React.FormEvent) => {
         e.preventDefault();
@@ -40,8 +55,41 @@ export default function Home() {
                 />
                 <HiddenElementsForm hiddenElements={hiddenElements} setHiddenElements={setHiddenElements} />
                 <BrowserSelect selectedBrowser={selectedBrowser} setSelectedBrowser={setSelectedBrowser} />
-                <ScreenshotButton url={url} hiddenElements={hiddenElements} />
+                <BackendSelect
+                    allowCdp={isChrome}
+                    backend={backend}
+                    setBackend={setBackend}
+                    androidSerial={androidSerial}
+                    setAndroidSerial={setAndroidSerial}
+                    useScreencap={useScreencap}
+                    setUseScreencap={setUseScreencap}
+                />
+                {backend === "webview" && (
+                    <WebviewForm
+                        webview={webview}
+                        setWebview={setWebview}
+                        androidSerial={androidSerial || undefined}
+                    />
+                )}
+                <CaptureOptionsForm
+                    toggles={toggles}
+                    setToggles={setToggles}
+                    setAdvancedOptions={setAdvancedOptions}
+                />
+                <ScreenshotButton
+                    url={url}
+                    options={{
+                        ...mergeCaptureOptions(advancedOptions, toggles),
+                        hiddenElements,
+                        browser: selectedBrowser.toLowerCase(),
+                        backend: backend === "cdp_adb" && !isChrome ? "appium" : backend,
+                        androidSerial: androidSerial || undefined,
+                        tileSource: isChrome && useScreencap ? "adb_screencap" : "webdriver",
+                        webview,
+                    }}
+                />
             </form>
+            <AppScreenshotButton />
         </div>
     );
 }